@@ -0,0 +1,410 @@
+//! The bytes_derive crate provides the `BinRead` and `BinWrite` derive macros, which generate
+//! `read_from` and `write_to` functions for structs, using the `EndianRead` and `EndianWrite`
+//! traits from the bytes crate, along with implementations of the `Decode` and `Encode` traits.
+//!
+//! Fields that are not primitive numbers or arrays are read and written using their `Decode`
+//! and `Encode` implementations.
+//!
+//! The following attributes are recognised:
+//!
+//! * `#[bin(big)]`, `#[bin(little)]` - on a struct or field, reads and writes using the given
+//!   byte order instead of the one of the wrapper passed in.
+//! * `#[bin(magic = b"...")]` - on a struct, reads and verifies, or writes, the given bytes
+//!   before the first field.
+//! * `#[bin(pad_before = N)]`, `#[bin(pad_after = N)]` - on a field, skips, or writes, N zero
+//!   bytes before or after the field.
+//! * `#[bin(count = field)]` - on a `Vec<T>` field, takes the number of elements from an
+//!   earlier integer field.
+
+extern crate proc_macro;
+extern crate proc_macro2;
+#[macro_use]
+extern crate quote;
+extern crate syn;
+
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use proc_macro2::TokenStream as Tokens;
+use syn::spanned::Spanned;
+
+#[derive(Clone, Copy)]
+enum Order {
+    Inherit,
+    Big,
+    Little,
+}
+
+struct StructAttrs {
+    order: Order,
+    magic: Option<syn::LitByteStr>,
+}
+
+struct FieldAttrs {
+    order: Order,
+    pad_before: Option<syn::Expr>,
+    pad_after: Option<syn::Expr>,
+    count: Option<syn::Ident>,
+}
+
+struct Field {
+    member: syn::Member,
+    local: syn::Ident,
+    ty: syn::Type,
+    attrs: FieldAttrs,
+}
+
+/// Derives a `read_from` function that reads the struct, field by field, from an `EndianRead`.
+#[proc_macro_derive(BinRead, attributes(bin))]
+pub fn derive_bin_read(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as syn::DeriveInput);
+    match bin_read(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/// Derives a `write_to` function that writes the struct, field by field, to an `EndianWrite`.
+#[proc_macro_derive(BinWrite, attributes(bin))]
+pub fn derive_bin_write(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as syn::DeriveInput);
+    match bin_write(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+fn bin_read(input: &syn::DeriveInput) -> syn::Result<Tokens> {
+    let attrs = struct_attrs(&input.attrs)?;
+    let (fields, named) = fields(input)?;
+    let mut body = Tokens::new();
+    if let Some(ref magic) = attrs.magic {
+        body.extend(quote! {
+            ::bytes::endian::EndianRead::expect_bytes(__reader, #magic)?;
+        });
+    }
+    for field in &fields {
+        if let Some(ref n) = field.attrs.pad_before {
+            body.extend(read_pad(n));
+        }
+        let local = &field.local;
+        let value = match field.attrs.count {
+            Some(ref count) => {
+                let elem = vec_elem(&field.ty)?;
+                let read = read_value(elem);
+                quote! {
+                    {
+                        let mut v = Vec::with_capacity((#count as usize)
+                                                           .min(::bytes::codec::MAX_PREALLOCATE));
+                        for _ in 0..#count {
+                            v.push(#read);
+                        }
+                        v
+                    }
+                }
+            }
+            None => read_value(&field.ty),
+        };
+        let value = with_reader_order(field.attrs.order, value);
+        body.extend(quote! {
+            let #local = #value;
+        });
+        if let Some(ref n) = field.attrs.pad_after {
+            body.extend(read_pad(n));
+        }
+    }
+    let name = &input.ident;
+    let members = fields.iter().map(|f| &f.member);
+    let locals = fields.iter().map(|f| &f.local);
+    let construct = if named {
+        quote! { #name { #(#members: #locals),* } }
+    } else {
+        quote! { #name ( #(#locals),* ) }
+    };
+    let body = with_reader_order(attrs.order,
+                                 quote! {
+                                     {
+                                         #body
+                                         #construct
+                                     }
+                                 });
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics #name #ty_generics #where_clause {
+            /// Reads the struct from the given EndianRead.
+            pub fn read_from<R: ::bytes::endian::EndianRead>(__reader: &mut R)
+                                                             -> ::std::io::Result<Self> {
+                Ok(#body)
+            }
+        }
+
+        impl #impl_generics ::bytes::codec::Decode for #name #ty_generics #where_clause {
+            fn decode<R: ::bytes::endian::EndianRead>(r: &mut R) -> ::std::io::Result<Self> {
+                #name::read_from(r)
+            }
+        }
+    })
+}
+
+fn bin_write(input: &syn::DeriveInput) -> syn::Result<Tokens> {
+    let attrs = struct_attrs(&input.attrs)?;
+    let (fields, _) = fields(input)?;
+    let mut body = Tokens::new();
+    if let Some(ref magic) = attrs.magic {
+        body.extend(quote! {
+            ::std::io::Write::write_all(__writer, #magic)?;
+        });
+    }
+    for field in &fields {
+        if let Some(ref n) = field.attrs.pad_before {
+            body.extend(write_pad(n));
+        }
+        let member = &field.member;
+        let value = match field.attrs.count {
+            Some(ref count) => {
+                let elem = vec_elem(&field.ty)?;
+                let write = write_value(elem, quote!(v));
+                quote! {
+                    {
+                        if self.#member.len() != self.#count as usize {
+                            return Err(::std::io::Error::new(::std::io::ErrorKind::InvalidInput,
+                                                             concat!("length of ",
+                                                                     stringify!(#member),
+                                                                     " does not match ",
+                                                                     stringify!(#count))));
+                        }
+                        for v in self.#member.iter() {
+                            #write;
+                        }
+                    }
+                }
+            }
+            None => write_value(&field.ty, quote!(&self.#member)),
+        };
+        body.extend(with_writer_order(field.attrs.order, quote!(#value;)));
+        if let Some(ref n) = field.attrs.pad_after {
+            body.extend(write_pad(n));
+        }
+    }
+    let body = with_writer_order(attrs.order, quote!({ #body }));
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics #name #ty_generics #where_clause {
+            /// Writes the struct to the given EndianWrite.
+            pub fn write_to<W: ::bytes::endian::EndianWrite>(&self, __writer: &mut W)
+                                                             -> ::std::io::Result<()> {
+                #body
+                Ok(())
+            }
+        }
+
+        impl #impl_generics ::bytes::codec::Encode for #name #ty_generics #where_clause {
+            fn encode<W: ::bytes::endian::EndianWrite>(&self, w: &mut W)
+                                                       -> ::std::io::Result<()> {
+                self.write_to(w)
+            }
+        }
+    })
+}
+
+/// Generates an expression that reads a value of the given type from `__reader`.
+fn read_value(ty: &syn::Type) -> Tokens {
+    if let Some(prim) = primitive(ty) {
+        let method = syn::Ident::new(&format!("read_{}", prim), Span::call_site());
+        return quote! { ::bytes::endian::EndianRead::#method(__reader)? };
+    }
+    if let syn::Type::Array(ref array) = *ty {
+        let elem = &array.elem;
+        let len = &array.len;
+        if primitive(elem).as_deref() == Some("u8") {
+            return quote! {
+                {
+                    let mut a = [0u8; #len];
+                    ::std::io::Read::read_exact(__reader, &mut a)?;
+                    a
+                }
+            };
+        }
+        let read = read_value(elem);
+        return quote! {
+            {
+                let mut v = Vec::with_capacity(#len);
+                for _ in 0..#len {
+                    v.push(#read);
+                }
+                match <#ty as ::std::convert::TryFrom<Vec<#elem>>>::try_from(v) {
+                    Ok(a) => a,
+                    Err(_) => unreachable!(),
+                }
+            }
+        };
+    }
+    quote! { <#ty as ::bytes::codec::Decode>::decode(__reader)? }
+}
+
+/// Generates an expression that writes the value referenced by `value` to `__writer`.
+fn write_value(ty: &syn::Type, value: Tokens) -> Tokens {
+    if let Some(prim) = primitive(ty) {
+        let method = syn::Ident::new(&format!("write_{}", prim), Span::call_site());
+        return quote! { ::bytes::endian::EndianWrite::#method(__writer, *#value)? };
+    }
+    if let syn::Type::Array(ref array) = *ty {
+        let write = write_value(&array.elem, quote!(v));
+        return quote! {
+            for v in (#value).iter() {
+                #write;
+            }
+        };
+    }
+    quote! { ::bytes::codec::Encode::encode(#value, __writer)? }
+}
+
+fn read_pad(n: &syn::Expr) -> Tokens {
+    quote! {
+        ::std::io::Read::read_exact(__reader, &mut [0u8; #n])?;
+    }
+}
+
+fn write_pad(n: &syn::Expr) -> Tokens {
+    quote! {
+        ::std::io::Write::write_all(__writer, &[0u8; #n])?;
+    }
+}
+
+fn with_reader_order(order: Order, tokens: Tokens) -> Tokens {
+    let wrapper = match order {
+        Order::Inherit => return tokens,
+        Order::Big => quote!(::bytes::endian::big::Read),
+        Order::Little => quote!(::bytes::endian::little::Read),
+    };
+    quote! {
+        {
+            let __reader = &mut #wrapper::new(&mut *__reader);
+            #tokens
+        }
+    }
+}
+
+fn with_writer_order(order: Order, tokens: Tokens) -> Tokens {
+    let wrapper = match order {
+        Order::Inherit => return tokens,
+        Order::Big => quote!(::bytes::endian::big::Write),
+        Order::Little => quote!(::bytes::endian::little::Write),
+    };
+    quote! {
+        {
+            let __writer = &mut #wrapper::new(&mut *__writer);
+            #tokens
+        }
+    }
+}
+
+/// Returns the name of the primitive number type, if the type is one.
+fn primitive(ty: &syn::Type) -> Option<String> {
+    if let syn::Type::Path(ref path) = *ty {
+        if let Some(ident) = path.path.get_ident() {
+            let name = ident.to_string();
+            match name.as_str() {
+                "u8" | "u16" | "u32" | "u64" | "i8" | "i16" | "i32" | "i64" | "f32" | "f64" => {
+                    return Some(name)
+                }
+                _ => {}
+            }
+        }
+    }
+    None
+}
+
+/// Returns the element type of a `Vec<T>`.
+fn vec_elem(ty: &syn::Type) -> syn::Result<&syn::Type> {
+    if let syn::Type::Path(ref path) = *ty {
+        if let Some(segment) = path.path.segments.last() {
+            if segment.ident == "Vec" {
+                if let syn::PathArguments::AngleBracketed(ref args) = segment.arguments {
+                    if let Some(syn::GenericArgument::Type(ref elem)) = args.args.first() {
+                        return Ok(elem);
+                    }
+                }
+            }
+        }
+    }
+    Err(syn::Error::new(ty.span(), "`count` can only be used on a Vec<T> field"))
+}
+
+fn fields(input: &syn::DeriveInput) -> syn::Result<(Vec<Field>, bool)> {
+    let data = match input.data {
+        syn::Data::Struct(ref data) => data,
+        _ => {
+            return Err(syn::Error::new(input.ident.span(),
+                                       "BinRead and BinWrite can only be derived for structs"))
+        }
+    };
+    let named = matches!(data.fields, syn::Fields::Named(_));
+    let mut fields = Vec::new();
+    for (i, field) in data.fields.iter().enumerate() {
+        let (member, local) = match field.ident {
+            Some(ref ident) => (syn::Member::Named(ident.clone()), ident.clone()),
+            None => {
+                (syn::Member::Unnamed(syn::Index::from(i)),
+                 syn::Ident::new(&format!("__field{}", i), Span::call_site()))
+            }
+        };
+        fields.push(Field {
+            member,
+            local,
+            ty: field.ty.clone(),
+            attrs: field_attrs(&field.attrs)?,
+        });
+    }
+    Ok((fields, named))
+}
+
+fn struct_attrs(attrs: &[syn::Attribute]) -> syn::Result<StructAttrs> {
+    let mut parsed = StructAttrs {
+        order: Order::Inherit,
+        magic: None,
+    };
+    for attr in attrs.iter().filter(|a| a.path().is_ident("bin")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("big") {
+                parsed.order = Order::Big;
+            } else if meta.path.is_ident("little") {
+                parsed.order = Order::Little;
+            } else if meta.path.is_ident("magic") {
+                parsed.magic = Some(meta.value()?.parse()?);
+            } else {
+                return Err(meta.error("unknown bin attribute"));
+            }
+            Ok(())
+        })?;
+    }
+    Ok(parsed)
+}
+
+fn field_attrs(attrs: &[syn::Attribute]) -> syn::Result<FieldAttrs> {
+    let mut parsed = FieldAttrs {
+        order: Order::Inherit,
+        pad_before: None,
+        pad_after: None,
+        count: None,
+    };
+    for attr in attrs.iter().filter(|a| a.path().is_ident("bin")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("big") {
+                parsed.order = Order::Big;
+            } else if meta.path.is_ident("little") {
+                parsed.order = Order::Little;
+            } else if meta.path.is_ident("pad_before") {
+                parsed.pad_before = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("pad_after") {
+                parsed.pad_after = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("count") {
+                parsed.count = Some(meta.value()?.parse()?);
+            } else {
+                return Err(meta.error("unknown bin attribute"));
+            }
+            Ok(())
+        })?;
+    }
+    Ok(parsed)
+}
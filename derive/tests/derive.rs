@@ -0,0 +1,133 @@
+extern crate bytes;
+#[macro_use]
+extern crate bytes_derive;
+
+use bytes::buffer::Buffer;
+use bytes::endian::big;
+use bytes::endian::little;
+
+#[derive(BinRead, BinWrite, Debug, PartialEq)]
+#[bin(magic = b"HDR\0")]
+struct Header {
+    version: u16,
+    #[bin(little)]
+    flags: u32,
+    #[bin(pad_before = 2)]
+    offset: i64,
+    id: [u8; 4],
+    scale: [f32; 2],
+    count: u8,
+    #[bin(count = count, pad_after = 1)]
+    entries: Vec<Entry>,
+}
+
+#[derive(BinRead, BinWrite, Debug, PartialEq)]
+struct Entry(u8, i16);
+
+#[derive(BinRead, BinWrite, Debug, PartialEq)]
+#[bin(big)]
+struct Always {
+    value: u32,
+}
+
+fn header() -> Header {
+    Header {
+        version: 0x0102,
+        flags: 0x0304_0506,
+        offset: -2,
+        id: *b"ABCD",
+        scale: [1.0, -0.5],
+        count: 2,
+        entries: vec![Entry(1, -1), Entry(2, 0x0304)],
+    }
+}
+
+const HEADER_BYTES: [u8; 40] = [b'H', b'D', b'R', 0, 1, 2, 6, 5, 4, 3, 0, 0, 255, 255, 255, 255,
+                                255, 255, 255, 254, b'A', b'B', b'C', b'D', 63, 128, 0, 0, 191,
+                                0, 0, 0, 2, 1, 255, 255, 2, 3, 4, 0];
+
+#[test]
+fn read_test() {
+    let mut reader = big::Read::new(Buffer::new(HEADER_BYTES.to_vec()));
+
+    assert_eq!(Header::read_from(&mut reader).expect("unexpected error"), header());
+}
+
+#[test]
+fn write_test() {
+    let mut writer = big::Write::new(Buffer::new(Vec::new()));
+
+    header().write_to(&mut writer).expect("unexpected error");
+    assert_eq!(**writer, HEADER_BYTES.to_vec());
+}
+
+#[test]
+fn magic_test() {
+    let mut reader = big::Read::new(Buffer::new(b"HDX\0".to_vec()));
+    let err = Header::read_from(&mut reader).expect_err("expected error");
+
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn count_mismatch_test() {
+    let mut h = header();
+    h.count = 3;
+
+    let mut writer = big::Write::new(Buffer::new(Vec::new()));
+
+    assert!(h.write_to(&mut writer).is_err());
+}
+
+#[derive(BinRead, BinWrite, Debug)]
+struct Counted {
+    n: u32,
+    #[bin(count = n)]
+    values: Vec<u64>,
+}
+
+#[test]
+fn huge_count_test() {
+    let mut reader = big::Read::new(Buffer::new(vec![0xFF, 0xFF, 0xFF, 0xFF, 0, 0]));
+    let err = Counted::read_from(&mut reader).expect_err("expected error");
+
+    assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+}
+
+#[test]
+fn struct_order_test() {
+    let mut writer = little::Write::new(Buffer::new(Vec::new()));
+
+    Always { value: 1 }.write_to(&mut writer).expect("unexpected error");
+    assert_eq!(**writer, vec![0, 0, 0, 1]);
+
+    let mut reader = little::Read::new(Buffer::new(vec![0, 0, 0, 2]));
+
+    assert_eq!(Always::read_from(&mut reader).expect("unexpected error"),
+               Always { value: 2 });
+}
+
+#[derive(BinRead, BinWrite, Debug, PartialEq)]
+struct Record {
+    name: String,
+    parent: Option<Entry>,
+}
+
+#[test]
+fn codec_test() {
+    use bytes::endian::EndianRead;
+    use bytes::endian::EndianWrite;
+
+    let record = Record {
+        name: String::from("ab"),
+        parent: Some(Entry(1, 2)),
+    };
+    let mut writer = big::Write::new(Buffer::new(Vec::new()));
+
+    writer.write_value(&record).expect("unexpected error");
+    assert_eq!(**writer, vec![0, 0, 0, 2, b'a', b'b', 1, 1, 0, 2]);
+
+    let mut reader = big::Read::new(Buffer::new((**writer).clone()));
+
+    assert_eq!(reader.read_value::<Record>().expect("unexpected error"), record);
+}
@@ -0,0 +1,450 @@
+//! The base module provides streaming Base64 and Base32 (RFC 4648) encoding and decoding.
+
+use std::io;
+use std::ops;
+use pending::Pending;
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const BASE64_URL_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+const BASE32_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+/// The value in a decoding table of a byte that is not in the alphabet.
+const INVALID: u8 = 0xFF;
+
+const fn decode_table(alphabet: &[u8]) -> [u8; 256] {
+    let mut table = [INVALID; 256];
+    let mut i = 0;
+    while i < alphabet.len() {
+        table[alphabet[i] as usize] = i as u8;
+        i += 1;
+    }
+    table
+}
+
+const BASE64_VALUES: [u8; 256] = decode_table(BASE64_ALPHABET);
+const BASE64_URL_VALUES: [u8; 256] = decode_table(BASE64_URL_ALPHABET);
+const BASE32_VALUES: [u8; 256] = decode_table(BASE32_ALPHABET);
+
+/// The Encoding struct describes a Base64 or Base32 alphabet, and whether encoded data is padded
+/// with '=' to a whole number of groups.
+#[derive(Clone, Copy)]
+pub struct Encoding {
+    alphabet: &'static [u8],
+    values: &'static [u8; 256],
+    bits: u32,
+    group: usize,
+    padding: bool,
+}
+
+/// Base64 with the standard alphabet, and padding.
+pub const BASE64: Encoding = Encoding {
+    alphabet: BASE64_ALPHABET,
+    values: &BASE64_VALUES,
+    bits: 6,
+    group: 4,
+    padding: true,
+};
+
+/// Base64 with the standard alphabet, and no padding.
+pub const BASE64_NO_PAD: Encoding = Encoding {
+    padding: false,
+    ..BASE64
+};
+
+/// Base64 with the URL and filename safe alphabet, and padding.
+pub const BASE64_URL: Encoding = Encoding {
+    alphabet: BASE64_URL_ALPHABET,
+    values: &BASE64_URL_VALUES,
+    ..BASE64
+};
+
+/// Base64 with the URL and filename safe alphabet, and no padding.
+pub const BASE64_URL_NO_PAD: Encoding = Encoding {
+    padding: false,
+    ..BASE64_URL
+};
+
+/// Base32 with the standard alphabet, and padding.
+pub const BASE32: Encoding = Encoding {
+    alphabet: BASE32_ALPHABET,
+    values: &BASE32_VALUES,
+    bits: 5,
+    group: 8,
+    padding: true,
+};
+
+/// Base32 with the standard alphabet, and no padding.
+pub const BASE32_NO_PAD: Encoding = Encoding {
+    padding: false,
+    ..BASE32
+};
+
+impl Encoding {
+    /// Returns true if encoded data is padded.
+    pub fn padding(&self) -> bool {
+        self.padding
+    }
+    /// Returns the given bytes encoded.
+    pub fn encode(&self, data: &[u8]) -> String {
+        let mut encoder = Encoder::new(Vec::new(), *self);
+        let _ = io::Write::write_all(&mut encoder, data);
+        let _ = encoder.finish();
+        String::from_utf8(encoder.writer).unwrap_or_default()
+    }
+    /// Returns the bytes encoded in the given string, ignoring any whitespace.
+    pub fn decode(&self, s: &str) -> io::Result<Vec<u8>> {
+        let mut data = Vec::with_capacity(s.len());
+        match io::Read::read_to_end(&mut Decoder::new(s.as_bytes(), *self), &mut data) {
+            Ok(_) => Ok(data),
+            Err(e) => Err(e),
+        }
+    }
+    fn mask(&self) -> u32 {
+        (1 << self.bits) - 1
+    }
+}
+
+/// The Encoder struct wraps an io::Write, writing everything written to it in the given
+/// Encoding.
+///
+/// Bytes that do not fill a whole character are held until more are written, so `finish` must
+/// be called once all data has been written. Characters that the embedded io::Write does not
+/// accept are held until a later call writes them.
+pub struct Encoder<W: io::Write> {
+    writer: W,
+    encoding: Encoding,
+    acc: u32,
+    bits: u32,
+    chars: usize,
+    pending: Pending,
+}
+
+impl<W: io::Write> Encoder<W> {
+    /// Constructs a new `Encoder<W>`, wrapping the given io::Write and using the given Encoding.
+    pub fn new(writer: W, encoding: Encoding) -> Encoder<W> {
+        Encoder {
+            writer,
+            encoding,
+            acc: 0,
+            bits: 0,
+            chars: 0,
+            pending: Pending::new(),
+        }
+    }
+    /// Writes any held bits, followed by padding if the Encoding requires it.
+    ///
+    /// Anything written afterwards is encoded as a new, separate, stream. If this returns an
+    /// error, calling it again finishes writing the same stream.
+    pub fn finish(&mut self) -> io::Result<()> {
+        if self.bits > 0 {
+            let v = (self.acc << (self.encoding.bits - self.bits)) & self.encoding.mask();
+            self.pending.push(self.encoding.alphabet[v as usize]);
+            self.chars += 1;
+        }
+        if self.encoding.padding {
+            while !self.chars.is_multiple_of(self.encoding.group) {
+                self.pending.push(b'=');
+                self.chars += 1;
+            }
+        }
+        self.acc = 0;
+        self.bits = 0;
+        self.chars = 0;
+        self.pending.drain(&mut self.writer)
+    }
+}
+
+impl<W: io::Write> io::Write for Encoder<W> {
+    /// The write function encodes the given bytes, writing all whole characters to the embedded
+    /// io::Write.
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.pending.drain(&mut self.writer)?;
+        for &b in buf {
+            self.acc = self.acc << 8 | b as u32;
+            self.bits += 8;
+            while self.bits >= self.encoding.bits {
+                self.bits -= self.encoding.bits;
+                let v = (self.acc >> self.bits) & self.encoding.mask();
+                self.pending.push(self.encoding.alphabet[v as usize]);
+                self.chars = (self.chars + 1) % self.encoding.group;
+            }
+            self.acc &= (1 << self.bits) - 1;
+        }
+        self.pending.accept(&mut self.writer, buf.len())
+    }
+    /// The flush function writes any whole characters that have not yet been written, and
+    /// forwards the call to the embedded io::Write, without writing any held bits.
+    fn flush(&mut self) -> io::Result<()> {
+        self.pending.drain(&mut self.writer)?;
+        self.writer.flush()
+    }
+}
+
+impl<W: io::Write> ops::Deref for Encoder<W> {
+    type Target = W;
+
+    /// The deref function allows access to the wrapped io::Write.
+    fn deref(&self) -> &W {
+        &self.writer
+    }
+}
+
+/// The Decoder struct wraps an io::Read of text in the given Encoding, reading the bytes it
+/// encodes.
+///
+/// Whitespace is ignored, and padding is accepted whether or not the Encoding requires it. Once
+/// a padded group is complete, any following text is decoded as a new, separate, stream, as
+/// written by `Encoder::finish`.
+pub struct Decoder<R: io::Read> {
+    reader: R,
+    encoding: Encoding,
+    acc: u32,
+    bits: u32,
+    chars: usize,
+    padded: bool,
+    error: Option<io::Error>,
+}
+
+impl<R: io::Read> Decoder<R> {
+    /// Constructs a new `Decoder<R>`, wrapping the given io::Read and using the given Encoding.
+    pub fn new(reader: R, encoding: Encoding) -> Decoder<R> {
+        Decoder {
+            reader,
+            encoding,
+            acc: 0,
+            bits: 0,
+            chars: 0,
+            padded: false,
+            error: None,
+        }
+    }
+    fn end(&mut self) -> io::Result<()> {
+        if self.bits >= self.encoding.bits {
+            return Err(invalid("truncated character group"));
+        }
+        self.acc = 0;
+        self.bits = 0;
+        Ok(())
+    }
+    fn decode(&mut self, c: u8) -> io::Result<Option<u8>> {
+        self.chars = (self.chars + 1) % self.encoding.group;
+        if c == b'=' {
+            if !self.padded {
+                if self.chars == 1 {
+                    return Err(invalid("unexpected padding"));
+                }
+                self.end()?;
+                self.padded = true;
+            }
+            if self.chars == 0 {
+                self.padded = false;
+            }
+            return Ok(None);
+        }
+        if self.padded {
+            return Err(invalid("data after padding"));
+        }
+        let v = self.encoding.values[c as usize];
+        if v == INVALID {
+            return Err(invalid("invalid character"));
+        }
+        self.acc = self.acc << self.encoding.bits | v as u32;
+        self.bits += self.encoding.bits;
+        if self.bits < 8 {
+            return Ok(None);
+        }
+        self.bits -= 8;
+        let b = (self.acc >> self.bits) as u8;
+        self.acc &= (1 << self.bits) - 1;
+        Ok(Some(b))
+    }
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+impl<R: io::Read> io::Read for Decoder<R> {
+    /// The read function reads encoded text from the embedded io::Read, decoding it into the
+    /// given slice.
+    ///
+    /// If invalid text follows bytes that have already been decoded, those bytes are returned,
+    /// and the error is returned by the next call.
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if let Some(e) = self.error.take() {
+            return Err(e);
+        }
+        let mut scratch = [0; 512];
+        let limit = if buf.len() < scratch.len() {
+            buf.len()
+        } else {
+            scratch.len()
+        };
+        let mut n = 0;
+        while n == 0 && limit > 0 {
+            let m = self.reader.read(&mut scratch[..limit])?;
+            if m == 0 {
+                self.end()?;
+                break;
+            }
+            for &c in &scratch[..m] {
+                if c.is_ascii_whitespace() {
+                    continue;
+                }
+                match self.decode(c) {
+                    Ok(Some(b)) => {
+                        buf[n] = b;
+                        n += 1;
+                    }
+                    Ok(None) => {}
+                    Err(e) => {
+                        if n == 0 {
+                            return Err(e);
+                        }
+                        self.error = Some(e);
+                        return Ok(n);
+                    }
+                }
+            }
+        }
+        Ok(n)
+    }
+}
+
+impl<R: io::Read> ops::Deref for Decoder<R> {
+    type Target = R;
+
+    /// The deref function allows access to the wrapped io::Read.
+    fn deref(&self) -> &R {
+        &self.reader
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+    use std::io::Read;
+    use std::io::Write;
+    use super::super::buffer::Buffer;
+    use super::super::endian::big;
+    use super::super::endian::EndianRead;
+    use super::super::endian::EndianWrite;
+    use super::super::sticky::write::Sticky;
+    use super::super::testing;
+    use super::Decoder;
+    use super::Encoder;
+    use super::BASE32;
+    use super::BASE32_NO_PAD;
+    use super::BASE64;
+    use super::BASE64_NO_PAD;
+    use super::BASE64_URL;
+    use super::BASE64_URL_NO_PAD;
+
+    #[test]
+    fn rfc4648_test() {
+        let tests = [("", "", ""),
+                     ("f", "Zg==", "MY======"),
+                     ("fo", "Zm8=", "MZXQ===="),
+                     ("foo", "Zm9v", "MZXW6==="),
+                     ("foob", "Zm9vYg==", "MZXW6YQ="),
+                     ("fooba", "Zm9vYmE=", "MZXW6YTB"),
+                     ("foobar", "Zm9vYmFy", "MZXW6YTBOI======")];
+
+        for &(data, base64, base32) in &tests {
+            assert_eq!(BASE64.encode(data.as_bytes()), base64);
+            assert_eq!(BASE32.encode(data.as_bytes()), base32);
+            assert_eq!(BASE64_NO_PAD.encode(data.as_bytes()), base64.trim_end_matches('='));
+            assert_eq!(BASE32_NO_PAD.encode(data.as_bytes()), base32.trim_end_matches('='));
+            assert_eq!(BASE64.decode(base64).expect("unexpected error"), data.as_bytes());
+            assert_eq!(BASE32.decode(base32).expect("unexpected error"), data.as_bytes());
+            assert_eq!(BASE64.decode(base64.trim_end_matches('=')).expect("unexpected error"),
+                       data.as_bytes());
+            assert_eq!(BASE32_NO_PAD.decode(base32.trim_end_matches('='))
+                                    .expect("unexpected error"),
+                       data.as_bytes());
+        }
+    }
+
+    #[test]
+    fn url_test() {
+        assert_eq!(BASE64.encode(&[0xFB, 0xFF]), "+/8=");
+        assert_eq!(BASE64_URL.encode(&[0xFB, 0xFF]), "-_8=");
+        assert_eq!(BASE64_URL_NO_PAD.encode(&[0xFB, 0xFF]), "-_8");
+        assert_eq!(BASE64_URL.decode("-_8").expect("unexpected error"), vec![0xFB, 0xFF]);
+        assert!(BASE64.decode("-_8").is_err());
+    }
+
+    #[test]
+    fn stream_test() {
+        let mut encoder = Encoder::new(Sticky::new(Buffer::new(Vec::new())), BASE64);
+
+        {
+            let mut writer = big::Write::new(&mut encoder);
+
+            writer.write_u16(0x6669).expect("unexpected error");
+            writer.write_u8(0x6C).expect("unexpected error");
+            writer.write_u8(0x65).expect("unexpected error");
+        }
+
+        assert_eq!(***encoder, b"ZmlsZ".to_vec());
+        encoder.finish().expect("unexpected error");
+        assert_eq!(***encoder, b"ZmlsZQ==".to_vec());
+
+        let mut decoder = big::Read::new(Decoder::new(Buffer::new(b"Zmls\r\nZQ==".to_vec()),
+                                                      BASE64));
+
+        assert_eq!(decoder.read_u32().expect("unexpected error"), 0x66696C65);
+        assert_eq!(decoder.read(&mut [0; 4]).expect("unexpected error"), 0);
+    }
+
+    #[test]
+    fn invalid_test() {
+        assert_eq!(BASE64.decode("Zg=a").expect_err("expected error").kind(),
+                   io::ErrorKind::InvalidData);
+        assert_eq!(BASE64.decode("Z").expect_err("expected error").kind(),
+                   io::ErrorKind::InvalidData);
+        assert_eq!(BASE64.decode("Z===").expect_err("expected error").kind(),
+                   io::ErrorKind::InvalidData);
+        assert_eq!(BASE32.decode("MZX").expect_err("expected error").kind(),
+                   io::ErrorKind::InvalidData);
+        assert_eq!(BASE32.decode("mzxq").expect_err("expected error").kind(),
+                   io::ErrorKind::InvalidData);
+        assert_eq!(BASE64.decode("Zm9v=").expect_err("expected error").kind(),
+                   io::ErrorKind::InvalidData);
+        assert_eq!(BASE64.decode("Zg===").expect_err("expected error").kind(),
+                   io::ErrorKind::InvalidData);
+
+        let mut writer = Encoder::new(Vec::new(), BASE32);
+
+        writer.write_all(b"f").expect("unexpected error");
+        writer.finish().expect("unexpected error");
+        writer.write_all(b"f").expect("unexpected error");
+        writer.finish().expect("unexpected error");
+        assert_eq!(*writer, b"MY======MY======".to_vec());
+        assert_eq!(BASE32.decode("MY======MY======").expect("unexpected error"), b"ff".to_vec());
+        assert_eq!(BASE64.decode("Zg==Zw==").expect("unexpected error"), b"fg".to_vec());
+        assert_eq!(BASE64.decode("Zg==Zm8=Zg").expect("unexpected error"), b"ffof".to_vec());
+    }
+
+    #[test]
+    fn progress_test() {
+        let mut decoder = Decoder::new(&b"Zm9vYmFy!"[..], BASE64);
+        let mut buf = [0; 16];
+
+        assert_eq!(decoder.read(&mut buf).expect("unexpected error"), 6);
+        assert_eq!(&buf[..6], b"foobar");
+        assert_eq!(decoder.read(&mut buf).expect_err("expected error").kind(),
+                   io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn chunked_test() {
+        let mut encoder = Encoder::new(testing::blocking(&[1, 2, 6, 7]), BASE64);
+
+        testing::write_retrying(&mut encoder, b"file").expect("unexpected error");
+        testing::retry(|| encoder.finish()).expect("unexpected error");
+        assert_eq!(encoder.to_vec(), b"ZmlsZQ==".to_vec());
+    }
+}
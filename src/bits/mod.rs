@@ -0,0 +1,307 @@
+use std::io;
+use std::ops;
+
+/// The maximum number of leading zero bits accepted when reading an Exp-Golomb code.
+const MAX_EXP_GOLOMB_ZEROS: u32 = 31;
+
+/// The BitOrder enum determines in which order bits are taken from, or placed into, each byte.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BitOrder {
+    /// Bits are taken from the most significant bit of each byte first, with the first bit
+    /// read becoming the most significant bit of a multi-bit value, as used by H.264 and FLAC.
+    MsbFirst,
+    /// Bits are taken from the least significant bit of each byte first, with the first bit
+    /// read becoming the least significant bit of a multi-bit value, as used by DEFLATE.
+    LsbFirst,
+}
+
+/// The BitReader struct wraps a type that implements the io::Read trait to provide bit-level
+/// reading functions.
+pub struct BitReader<T: io::Read> {
+    reader: T,
+    order: BitOrder,
+    byte: u8,
+    remaining: u8,
+}
+
+impl<T: io::Read> BitReader<T> {
+    /// Constructs a new `BitReader<T>`, wrapping the given io::Read and reading bits in the
+    /// given order.
+    pub fn new(reader: T, order: BitOrder) -> BitReader<T> {
+        BitReader {
+            reader,
+            order,
+            byte: 0,
+            remaining: 0,
+        }
+    }
+    /// Read a single bit from the reader.
+    pub fn read_bit(&mut self) -> io::Result<bool> {
+        if self.remaining == 0 {
+            let mut buffer = [0];
+            self.reader.read_exact(&mut buffer)?;
+            self.byte = buffer[0];
+            self.remaining = 8;
+        }
+        self.remaining -= 1;
+        let shift = match self.order {
+            BitOrder::MsbFirst => self.remaining,
+            BitOrder::LsbFirst => 7 - self.remaining,
+        };
+        Ok((self.byte >> shift) & 1 == 1)
+    }
+    /// Read `n` bits, up to a maximum of 64, from the reader.
+    pub fn read_bits(&mut self, n: u32) -> io::Result<u64> {
+        if n > 64 {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                      "cannot read more than 64 bits at once"));
+        }
+        let mut v = 0u64;
+        for i in 0..n {
+            let bit = self.read_bit()? as u64;
+            match self.order {
+                BitOrder::MsbFirst => v = v << 1 | bit,
+                BitOrder::LsbFirst => v |= bit << i,
+            }
+        }
+        Ok(v)
+    }
+    /// Discards any remaining bits in the current byte, so that the next read starts on a byte
+    /// boundary.
+    pub fn align_to_byte(&mut self) {
+        self.remaining = 0;
+    }
+    /// Returns true if the next read will start on a byte boundary.
+    pub fn is_aligned(&self) -> bool {
+        self.remaining == 0
+    }
+    /// Read an unsigned Exp-Golomb code, ue(v), from the reader, as used by H.264 and HEVC.
+    ///
+    /// Codes with more than 31 leading zero bits cannot be represented and result in an
+    /// InvalidData error, so that a corrupt stream cannot cause an unbounded read.
+    pub fn read_ue(&mut self) -> io::Result<u32> {
+        let mut zeros = 0;
+        while !self.read_bit()? {
+            zeros += 1;
+            if zeros > MAX_EXP_GOLOMB_ZEROS {
+                return Err(io::Error::new(io::ErrorKind::InvalidData,
+                                          "exp-golomb code has too many leading zeros"));
+            }
+        }
+        let v = self.read_bits(zeros)?;
+        Ok(((1u64 << zeros) - 1 + v) as u32)
+    }
+    /// Read a signed Exp-Golomb code, se(v), from the reader, as used by H.264 and HEVC.
+    pub fn read_se(&mut self) -> io::Result<i32> {
+        let k = self.read_ue()? as i64;
+        if k & 1 == 1 {
+            Ok(((k + 1) / 2) as i32)
+        } else {
+            Ok((-k / 2) as i32)
+        }
+    }
+}
+
+impl<T: io::Read> ops::Deref for BitReader<T> {
+    type Target = T;
+
+    /// The deref function allows access to the wrapped io::Read.
+    fn deref(&self) -> &T {
+        &self.reader
+    }
+}
+
+/// The BitWriter struct wraps a type that implements the io::Write trait to provide bit-level
+/// writing functions.
+///
+/// A partially filled byte is not written until it is completed, or `align_to_byte` is called.
+pub struct BitWriter<T: io::Write> {
+    writer: T,
+    order: BitOrder,
+    byte: u8,
+    used: u8,
+}
+
+impl<T: io::Write> BitWriter<T> {
+    /// Constructs a new `BitWriter<T>`, wrapping the given io::Write and writing bits in the
+    /// given order.
+    pub fn new(writer: T, order: BitOrder) -> BitWriter<T> {
+        BitWriter {
+            writer,
+            order,
+            byte: 0,
+            used: 0,
+        }
+    }
+    /// Write a single bit to the writer.
+    pub fn write_bit(&mut self, bit: bool) -> io::Result<()> {
+        let shift = match self.order {
+            BitOrder::MsbFirst => 7 - self.used,
+            BitOrder::LsbFirst => self.used,
+        };
+        self.byte |= (bit as u8) << shift;
+        self.used += 1;
+        if self.used == 8 {
+            self.align_to_byte()
+        } else {
+            Ok(())
+        }
+    }
+    /// Write the lowest `n` bits, up to a maximum of 64, of `v` to the writer.
+    pub fn write_bits(&mut self, v: u64, n: u32) -> io::Result<()> {
+        if n > 64 {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                      "cannot write more than 64 bits at once"));
+        }
+        for i in 0..n {
+            let shift = match self.order {
+                BitOrder::MsbFirst => n - 1 - i,
+                BitOrder::LsbFirst => i,
+            };
+            self.write_bit((v >> shift) & 1 == 1)?;
+        }
+        Ok(())
+    }
+    /// Pads the current byte with zero bits and writes it, so that the next write starts on a
+    /// byte boundary. Does nothing if already aligned.
+    pub fn align_to_byte(&mut self) -> io::Result<()> {
+        if self.used == 0 {
+            return Ok(());
+        }
+        let buffer = [self.byte];
+        self.byte = 0;
+        self.used = 0;
+        self.writer.write_all(&buffer)
+    }
+    /// Returns true if the next write will start on a byte boundary.
+    pub fn is_aligned(&self) -> bool {
+        self.used == 0
+    }
+    /// Write an unsigned Exp-Golomb code, ue(v), to the writer.
+    ///
+    /// The value u32::MAX cannot be represented and results in an InvalidInput error.
+    pub fn write_ue(&mut self, v: u32) -> io::Result<()> {
+        if v == u32::MAX {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                      "value too large for exp-golomb code"));
+        }
+        let x = v as u64 + 1;
+        let zeros = 63 - x.leading_zeros();
+        self.write_bits(0, zeros)?;
+        self.write_bits(x, zeros + 1)
+    }
+    /// Write a signed Exp-Golomb code, se(v), to the writer.
+    ///
+    /// The value i32::MIN cannot be represented and results in an InvalidInput error.
+    pub fn write_se(&mut self, v: i32) -> io::Result<()> {
+        let v = v as i64;
+        let k = if v > 0 { 2 * v - 1 } else { -2 * v };
+        if k > u32::MAX as i64 - 1 {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                      "value too large for exp-golomb code"));
+        }
+        self.write_ue(k as u32)
+    }
+}
+
+impl<T: io::Write> ops::Deref for BitWriter<T> {
+    type Target = T;
+
+    /// The deref function allows access to the wrapped io::Write.
+    fn deref(&self) -> &T {
+        &self.writer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+    use super::super::buffer::Buffer;
+    use super::BitOrder;
+    use super::BitReader;
+    use super::BitWriter;
+
+    #[test]
+    fn read_msb_test() {
+        let mut reader = BitReader::new(Buffer::new(vec![0b1011_0010, 0b0111_1111, 0xFF]),
+                                        BitOrder::MsbFirst);
+
+        assert!(reader.read_bit().expect("unexpected error"));
+        assert_eq!(reader.read_bits(3).expect("unexpected error"), 0b011);
+        assert_eq!(reader.read_bits(6).expect("unexpected error"), 0b00_1001);
+        assert!(!reader.is_aligned());
+        reader.align_to_byte();
+        assert_eq!(reader.read_bits(8).expect("unexpected error"), 0xFF);
+        assert!(reader.read_bit().is_err());
+    }
+
+    #[test]
+    fn read_lsb_test() {
+        let mut reader = BitReader::new(Buffer::new(vec![0b1011_0010, 0b0111_1101]),
+                                        BitOrder::LsbFirst);
+
+        assert!(!reader.read_bit().expect("unexpected error"));
+        assert_eq!(reader.read_bits(3).expect("unexpected error"), 0b001);
+        assert_eq!(reader.read_bits(6).expect("unexpected error"), 0b01_1011);
+        assert!(reader.read_bits(65).is_err());
+    }
+
+    #[test]
+    fn write_test() {
+        for order in [BitOrder::MsbFirst, BitOrder::LsbFirst].iter() {
+            let mut writer = BitWriter::new(Buffer::new(Vec::new()), *order);
+
+            writer.write_bit(true).expect("unexpected error");
+            writer.write_bits(0b011, 3).expect("unexpected error");
+            writer.write_bits(0b1_0010_0111, 9).expect("unexpected error");
+            writer.align_to_byte().expect("unexpected error");
+            writer.write_bits(0xAB, 8).expect("unexpected error");
+
+            let mut reader = BitReader::new(Buffer::new((**writer).clone()), *order);
+
+            assert!(reader.read_bit().expect("unexpected error"));
+            assert_eq!(reader.read_bits(3).expect("unexpected error"), 0b011);
+            assert_eq!(reader.read_bits(9).expect("unexpected error"), 0b1_0010_0111);
+            assert_eq!(reader.read_bits(3).expect("unexpected error"), 0);
+            assert_eq!(reader.read_bits(8).expect("unexpected error"), 0xAB);
+        }
+    }
+
+    #[test]
+    fn exp_golomb_test() {
+        let mut reader = BitReader::new(Buffer::new(vec![0b1010_0110, 0b0100_0010, 0b0000_0000]),
+                                        BitOrder::MsbFirst);
+
+        assert_eq!(reader.read_ue().expect("unexpected error"), 0);
+        assert_eq!(reader.read_ue().expect("unexpected error"), 1);
+        assert_eq!(reader.read_ue().expect("unexpected error"), 2);
+        assert_eq!(reader.read_ue().expect("unexpected error"), 3);
+        assert_eq!(reader.read_se().expect("unexpected error"), 2);
+
+        let values = [0, 1, -1, 7, -7, 1000, -1000, i32::MAX, i32::MIN + 1];
+        let mut writer = BitWriter::new(Buffer::new(Vec::new()), BitOrder::MsbFirst);
+
+        writer.write_ue(u32::MAX - 1).expect("unexpected error");
+        for v in values.iter() {
+            writer.write_se(*v).expect("unexpected error");
+        }
+        assert!(writer.write_ue(u32::MAX).is_err());
+        assert!(writer.write_se(i32::MIN).is_err());
+        writer.align_to_byte().expect("unexpected error");
+
+        let mut reader = BitReader::new(Buffer::new((**writer).clone()), BitOrder::MsbFirst);
+
+        assert_eq!(reader.read_ue().expect("unexpected error"), u32::MAX - 1);
+        for v in values.iter() {
+            assert_eq!(reader.read_se().expect("unexpected error"), *v);
+        }
+    }
+
+    #[test]
+    fn exp_golomb_corrupt_test() {
+        let mut reader = BitReader::new(Buffer::new(vec![0; 16]), BitOrder::MsbFirst);
+
+        assert_eq!(reader.read_ue().expect_err("expected error").kind(),
+                   io::ErrorKind::InvalidData);
+    }
+}
@@ -1,4 +1,5 @@
 use std::io;
+use std::mem;
 use std::ops;
 
 /// The Buffer struct wraps a Vec<u8> to provide read and write
@@ -8,7 +9,22 @@ pub struct Buffer {
 
 impl Buffer {
     pub fn new(data: Vec<u8>) -> Buffer {
-        Buffer { data: data }
+        Buffer { data }
+    }
+    /// Removes and returns the bytes before the first occurrence of the given delimiter,
+    /// discarding the delimiter itself.
+    ///
+    /// Returns None, leaving the Buffer unchanged, if the delimiter does not occur, such as when
+    /// the rest of a line has not yet been written. An empty delimiter never occurs.
+    pub fn take_delimited(&mut self, delimiter: &[u8]) -> Option<Vec<u8>> {
+        if delimiter.is_empty() {
+            return None;
+        }
+        let pos = self.data.windows(delimiter.len()).position(|w| w == delimiter)?;
+        let rest = self.data.split_off(pos + delimiter.len());
+        let mut frame = mem::replace(&mut self.data, rest);
+        frame.truncate(pos);
+        Some(frame)
     }
 }
 
@@ -119,4 +135,18 @@ mod tests {
         assert_eq!(buffer.read(&mut buf).expect("unexpected error"), 3);
         assert_eq!(buf, String::from("ll!He").as_bytes());
     }
+
+    #[test]
+    fn take_delimited_test() {
+        let mut buffer = super::Buffer::new(b"GET /\r\nHost: a\r\n\r\nbo".to_vec());
+
+        assert_eq!(buffer.take_delimited(b"\r\n").expect("missing line"), b"GET /".to_vec());
+        assert_eq!(buffer.take_delimited(b"\r\n").expect("missing line"), b"Host: a".to_vec());
+        assert_eq!(buffer.take_delimited(b"\r\n").expect("missing line"), b"".to_vec());
+        assert_eq!(buffer.take_delimited(b"\r\n"), None);
+        assert_eq!(buffer.write(b"dy\n").expect("unexpected error"), 3);
+        assert_eq!(buffer.take_delimited(b""), None);
+        assert_eq!(buffer.take_delimited(b"\n").expect("missing line"), b"body".to_vec());
+        assert!(buffer.is_empty());
+    }
 }
@@ -0,0 +1,134 @@
+//! The chain module provides MultiReader, which reads a number of readers one after another as
+//! a single stream.
+
+use std::io;
+use position::Position;
+
+/// The MultiReader struct reads each of a Vec of io::Read in turn, moving on to the next when
+/// one reaches end of file, such as the parts of a split archive.
+///
+/// It keeps track of where each source started within the combined stream, so that a position
+/// within it can be mapped back to a source and an offset within that source.
+pub struct MultiReader<R: io::Read> {
+    readers: Vec<R>,
+    starts: Vec<u64>,
+    position: u64,
+}
+
+impl<R: io::Read> MultiReader<R> {
+    /// Constructs a new `MultiReader<R>`, reading from the given readers in order.
+    pub fn new(readers: Vec<R>) -> MultiReader<R> {
+        MultiReader {
+            readers,
+            starts: vec![0],
+            position: 0,
+        }
+    }
+    /// Returns the sources being read.
+    pub fn sources(&self) -> &[R] {
+        &self.readers
+    }
+    /// Returns the index of the source currently being read, and the offset within it.
+    ///
+    /// Once all sources have been read, this returns the index of the last source.
+    pub fn current(&self) -> (usize, u64) {
+        let index = self.starts.len() - 1;
+        (index, self.position - self.starts[index])
+    }
+    /// Returns the index of the source containing the given position within the combined
+    /// stream, and the offset within that source.
+    ///
+    /// Only positions that have already been reached can be mapped, as the lengths of sources
+    /// are not known until they have been read; None is returned for any later position.
+    pub fn locate(&self, position: u64) -> Option<(usize, u64)> {
+        if position > self.position {
+            return None;
+        }
+        let index = self.starts.iter().rposition(|&start| start <= position)?;
+        Some((index, position - self.starts[index]))
+    }
+}
+
+impl<R: io::Read> io::Read for MultiReader<R> {
+    /// The read function reads from the current source, moving on to the next source each time
+    /// one reaches end of file.
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        loop {
+            let index = self.starts.len() - 1;
+            let reader = match self.readers.get_mut(index) {
+                Some(reader) => reader,
+                None => return Ok(0),
+            };
+            let n = reader.read(buf)?;
+            if n > 0 {
+                self.position += n as u64;
+                return Ok(n);
+            }
+            if index + 1 == self.readers.len() {
+                return Ok(0);
+            }
+            self.starts.push(self.position);
+        }
+    }
+}
+
+impl<R: io::Read> Position for MultiReader<R> {
+    fn position(&self) -> u64 {
+        self.position
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+    use super::super::buffer::Buffer;
+    use super::super::endian::big;
+    use super::super::endian::little;
+    use super::super::endian::EndianRead;
+    use super::super::position::Position;
+    use super::MultiReader;
+
+    #[test]
+    fn read_test() {
+        let parts = vec![Buffer::new(vec![1, 2, 3]),
+                         Buffer::new(vec![]),
+                         Buffer::new(vec![4, 5]),
+                         Buffer::new(vec![6, 7, 8, 9])];
+        let mut reader = big::Read::new(MultiReader::new(parts));
+
+        assert_eq!(reader.read_u32().expect("unexpected error"), 0x01020304);
+        assert_eq!(reader.position(), 4);
+        assert_eq!(reader.current(), (2, 1));
+        assert_eq!(reader.read_u32().expect("unexpected error"), 0x05060708);
+        assert_eq!(reader.current(), (3, 3));
+        assert_eq!(reader.locate(0), Some((0, 0)));
+        assert_eq!(reader.locate(2), Some((0, 2)));
+        assert_eq!(reader.locate(3), Some((2, 0)));
+        assert_eq!(reader.locate(5), Some((3, 0)));
+        assert_eq!(reader.locate(8), Some((3, 3)));
+        assert_eq!(reader.locate(9), None);
+        assert_eq!(reader.read_u8().expect("unexpected error"), 9);
+        assert!(reader.read_u8().is_err());
+        assert_eq!(reader.sources().len(), 4);
+    }
+
+    #[test]
+    fn empty_test() {
+        let mut reader = MultiReader::new(Vec::<Buffer>::new());
+
+        assert_eq!(reader.read(&mut [0; 4]).expect("unexpected error"), 0);
+        assert_eq!(reader.current(), (0, 0));
+        assert_eq!(reader.locate(0), Some((0, 0)));
+
+        let mut reader = little::Read::new(MultiReader::new(vec![&[1, 2][..], &[3, 4, 5][..]]));
+        let mut buf = Vec::new();
+
+        assert_eq!(reader.read_u16().expect("unexpected error"), 0x0201);
+        reader.read_to_end(&mut buf).expect("unexpected error");
+        assert_eq!(buf, vec![3, 4, 5]);
+        assert_eq!(reader.current(), (1, 3));
+    }
+}
@@ -0,0 +1,398 @@
+use std::char;
+use std::convert::TryFrom;
+use std::io;
+use std::io::Read;
+use endian::EndianRead;
+use endian::EndianWrite;
+use fixed::F2Dot14;
+use fixed::Q16_16;
+use fixed::Q8_8;
+
+/// The maximum number of elements preallocated when decoding a length prefixed value, so that a
+/// corrupt length cannot cause a huge allocation before any data is read.
+///
+/// This is also used by the serde Deserializer, and by the code generated for
+/// `#[bin(count = field)]` by bytes_derive.
+pub const MAX_PREALLOCATE: usize = 4096;
+
+/// The Decode trait is implemented by types that can be read from an EndianRead, in the byte
+/// order of the reader.
+pub trait Decode: Sized {
+    /// Read a value of this type from the reader.
+    fn decode<R: EndianRead>(r: &mut R) -> io::Result<Self>;
+}
+
+/// The Encode trait is implemented by types that can be written to an EndianWrite, in the byte
+/// order of the writer.
+pub trait Encode {
+    /// Write this value to the writer.
+    fn encode<W: EndianWrite>(&self, w: &mut W) -> io::Result<()>;
+}
+
+macro_rules! primitive {
+    ($t:ty, $read:ident, $write:ident) => {
+        impl Decode for $t {
+            fn decode<R: EndianRead>(r: &mut R) -> io::Result<$t> {
+                r.$read()
+            }
+        }
+
+        impl Encode for $t {
+            fn encode<W: EndianWrite>(&self, w: &mut W) -> io::Result<()> {
+                w.$write(*self)
+            }
+        }
+    }
+}
+
+primitive!(u8, read_u8, write_u8);
+primitive!(u16, read_u16, write_u16);
+primitive!(u32, read_u32, write_u32);
+primitive!(u64, read_u64, write_u64);
+primitive!(i8, read_i8, write_i8);
+primitive!(i16, read_i16, write_i16);
+primitive!(i32, read_i32, write_i32);
+primitive!(i64, read_i64, write_i64);
+primitive!(f32, read_f32, write_f32);
+primitive!(f64, read_f64, write_f64);
+primitive!(Q16_16, read_q16_16, write_q16_16);
+primitive!(Q8_8, read_q8_8, write_q8_8);
+primitive!(F2Dot14, read_f2dot14, write_f2dot14);
+
+#[cfg(feature = "half")]
+impl Decode for ::half::f16 {
+    fn decode<R: EndianRead>(r: &mut R) -> io::Result<::half::f16> {
+        match r.read_u16() {
+            Ok(v) => Ok(::half::f16::from_bits(v)),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+#[cfg(feature = "half")]
+impl Encode for ::half::f16 {
+    fn encode<W: EndianWrite>(&self, w: &mut W) -> io::Result<()> {
+        w.write_u16(self.to_bits())
+    }
+}
+
+#[cfg(feature = "half")]
+impl Decode for ::half::bf16 {
+    fn decode<R: EndianRead>(r: &mut R) -> io::Result<::half::bf16> {
+        match r.read_u16() {
+            Ok(v) => Ok(::half::bf16::from_bits(v)),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+#[cfg(feature = "half")]
+impl Encode for ::half::bf16 {
+    fn encode<W: EndianWrite>(&self, w: &mut W) -> io::Result<()> {
+        w.write_u16(self.to_bits())
+    }
+}
+
+/// A bool is encoded as a single byte, either 0 or 1.
+impl Decode for bool {
+    fn decode<R: EndianRead>(r: &mut R) -> io::Result<bool> {
+        match r.read_u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            v => {
+                Err(io::Error::new(io::ErrorKind::InvalidData, format!("invalid bool: {}", v)))
+            }
+        }
+    }
+}
+
+impl Encode for bool {
+    fn encode<W: EndianWrite>(&self, w: &mut W) -> io::Result<()> {
+        w.write_u8(*self as u8)
+    }
+}
+
+/// A char is encoded as its 32-bit Unicode scalar value.
+impl Decode for char {
+    fn decode<R: EndianRead>(r: &mut R) -> io::Result<char> {
+        let v = r.read_u32()?;
+        match char::from_u32(v) {
+            Some(c) => Ok(c),
+            None => {
+                Err(io::Error::new(io::ErrorKind::InvalidData,
+                                   format!("invalid char: {:#x}", v)))
+            }
+        }
+    }
+}
+
+impl Encode for char {
+    fn encode<W: EndianWrite>(&self, w: &mut W) -> io::Result<()> {
+        w.write_u32(*self as u32)
+    }
+}
+
+impl Decode for () {
+    fn decode<R: EndianRead>(_: &mut R) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Encode for () {
+    fn encode<W: EndianWrite>(&self, _: &mut W) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// An array is encoded as each of its elements in order, with no length prefix.
+impl<T: Decode, const N: usize> Decode for [T; N] {
+    fn decode<R: EndianRead>(r: &mut R) -> io::Result<[T; N]> {
+        let mut v = Vec::with_capacity(N);
+        for _ in 0..N {
+            v.push(T::decode(r)?);
+        }
+        match <[T; N]>::try_from(v) {
+            Ok(a) => Ok(a),
+            Err(_) => unreachable!(),
+        }
+    }
+}
+
+impl<T: Encode, const N: usize> Encode for [T; N] {
+    fn encode<W: EndianWrite>(&self, w: &mut W) -> io::Result<()> {
+        for v in self.iter() {
+            v.encode(w)?;
+        }
+        Ok(())
+    }
+}
+
+/// An Option is encoded as a single byte, 0 for None and 1 for Some, followed by the value
+/// when present.
+impl<T: Decode> Decode for Option<T> {
+    fn decode<R: EndianRead>(r: &mut R) -> io::Result<Option<T>> {
+        if bool::decode(r)? {
+            Ok(Some(T::decode(r)?))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+impl<T: Encode> Encode for Option<T> {
+    fn encode<W: EndianWrite>(&self, w: &mut W) -> io::Result<()> {
+        match *self {
+            Some(ref v) => {
+                w.write_u8(1)?;
+                v.encode(w)
+            }
+            None => w.write_u8(0),
+        }
+    }
+}
+
+/// A Vec is encoded as an unsigned 32-bit length prefix, followed by each of its elements.
+impl<T: Decode> Decode for Vec<T> {
+    fn decode<R: EndianRead>(r: &mut R) -> io::Result<Vec<T>> {
+        let l = r.read_u32()? as usize;
+        let mut v = Vec::with_capacity(l.min(MAX_PREALLOCATE));
+        for _ in 0..l {
+            v.push(T::decode(r)?);
+        }
+        Ok(v)
+    }
+}
+
+impl<T: Encode> Encode for Vec<T> {
+    fn encode<W: EndianWrite>(&self, w: &mut W) -> io::Result<()> {
+        write_length(w, self.len())?;
+        for v in self.iter() {
+            v.encode(w)?;
+        }
+        Ok(())
+    }
+}
+
+/// A String is encoded as an unsigned 32-bit length prefix, followed by its UTF-8 bytes.
+impl Decode for String {
+    fn decode<R: EndianRead>(r: &mut R) -> io::Result<String> {
+        let l = r.read_u32()? as u64;
+        let mut buf = Vec::new();
+        if r.by_ref().take(l).read_to_end(&mut buf)? as u64 != l {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof,
+                                      "failed to fill whole buffer"));
+        }
+        match String::from_utf8(buf) {
+            Ok(s) => Ok(s),
+            Err(e) => Err(io::Error::new(io::ErrorKind::InvalidData, e)),
+        }
+    }
+}
+
+impl Encode for String {
+    fn encode<W: EndianWrite>(&self, w: &mut W) -> io::Result<()> {
+        self.as_str().encode(w)
+    }
+}
+
+impl Encode for str {
+    fn encode<W: EndianWrite>(&self, w: &mut W) -> io::Result<()> {
+        write_length(w, self.len())?;
+        w.write_all(self.as_bytes())
+    }
+}
+
+impl<T: Encode + ?Sized> Encode for &T {
+    fn encode<W: EndianWrite>(&self, w: &mut W) -> io::Result<()> {
+        (**self).encode(w)
+    }
+}
+
+fn write_length<W: EndianWrite>(w: &mut W, l: usize) -> io::Result<()> {
+    if l > u32::MAX as usize {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                  "length too large for 32-bit prefix"));
+    }
+    w.write_u32(l as u32)
+}
+
+macro_rules! tuple {
+    ($($t:ident $v:ident),+) => {
+        /// A tuple is encoded as each of its elements in order.
+        impl<$($t: Decode),+> Decode for ($($t,)+) {
+            fn decode<R: EndianRead>(r: &mut R) -> io::Result<($($t,)+)> {
+                Ok(($($t::decode(r)?,)+))
+            }
+        }
+
+        impl<$($t: Encode),+> Encode for ($($t,)+) {
+            fn encode<W: EndianWrite>(&self, w: &mut W) -> io::Result<()> {
+                let ($(ref $v,)+) = *self;
+                $($v.encode(w)?;)+
+                Ok(())
+            }
+        }
+    }
+}
+
+tuple!(A a);
+tuple!(A a, B b);
+tuple!(A a, B b, C c);
+tuple!(A a, B b, C c, D d);
+tuple!(A a, B b, C c, D d, E e);
+tuple!(A a, B b, C c, D d, E e, F f);
+tuple!(A a, B b, C c, D d, E e, F f, G g);
+tuple!(A a, B b, C c, D d, E e, F f, G g, H h);
+tuple!(A a, B b, C c, D d, E e, F f, G g, H h, I i);
+tuple!(A a, B b, C c, D d, E e, F f, G g, H h, I i, J j);
+tuple!(A a, B b, C c, D d, E e, F f, G g, H h, I i, J j, K k);
+tuple!(A a, B b, C c, D d, E e, F f, G g, H h, I i, J j, K k, L l);
+
+#[cfg(test)]
+mod tests {
+    use super::super::buffer::Buffer;
+    use super::super::endian::big;
+    use super::super::endian::little;
+    use super::super::endian::EndianRead;
+    use super::super::endian::EndianWrite;
+
+    #[test]
+    fn round_trip_test() {
+        let value = (1u8,
+                     -2i16,
+                     [3u32, 4],
+                     Some(5u64),
+                     None::<f32>,
+                     vec![(true, 'x'), (false, '\u{1F600}')],
+                     String::from("Hello"));
+        let mut writer = big::Write::new(Buffer::new(Vec::new()));
+
+        writer.write_value(&value).expect("unexpected error");
+
+        let mut reader = big::Read::new(Buffer::new((**writer).clone()));
+
+        assert_eq!(reader.read_value::<(u8,
+                                        i16,
+                                        [u32; 2],
+                                        Option<u64>,
+                                        Option<f32>,
+                                        Vec<(bool, char)>,
+                                        String)>()
+                       .expect("unexpected error"),
+                   value);
+        assert_eq!(reader.read_u8().expect_err("expected error").kind(),
+                   ::std::io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn byte_order_test() {
+        let mut writer = little::Write::new(Buffer::new(Vec::new()));
+
+        writer.write_value(&vec![0x0102u16]).expect("unexpected error");
+        assert_eq!(**writer, vec![1, 0, 0, 0, 2, 1]);
+
+        let mut writer = big::Write::new(Buffer::new(Vec::new()));
+
+        writer.write_value(&vec![0x0102u16]).expect("unexpected error");
+        assert_eq!(**writer, vec![0, 0, 0, 1, 1, 2]);
+    }
+
+    #[test]
+    fn invalid_test() {
+        let mut reader = big::Read::new(Buffer::new(vec![2, 0, 0, 0, 5, b'a']));
+
+        assert!(reader.read_value::<bool>().is_err());
+        assert!(reader.read_value::<String>().is_err());
+    }
+
+    #[cfg(feature = "half")]
+    #[test]
+    fn half_test() {
+        let data = vec![0x3C, 0x00, 0xC0, 0x00, 0x7B, 0xFF, 0x00, 0x01];
+        let mut big_values = big::Read::new(Buffer::new(data.clone()));
+        let mut big_floats = big::Read::new(Buffer::new(data.clone()));
+        let mut little_values = little::Read::new(Buffer::new(data.clone()));
+        let mut little_floats = little::Read::new(Buffer::new(data.clone()));
+
+        for _ in 0..4 {
+            let v = big_values.read_value::<::half::f16>().expect("unexpected error");
+
+            assert_eq!(v.to_f32().to_bits(),
+                       big_floats.read_f16().expect("unexpected error").to_bits());
+
+            let v = little_values.read_value::<::half::f16>().expect("unexpected error");
+
+            assert_eq!(v.to_f32().to_bits(),
+                       little_floats.read_f16().expect("unexpected error").to_bits());
+        }
+
+        let mut big_values = big::Read::new(Buffer::new(data.clone()));
+        let mut little_values = little::Read::new(Buffer::new(data.clone()));
+        let mut big_floats = big::Read::new(Buffer::new(data.clone()));
+        let mut little_floats = little::Read::new(Buffer::new(data));
+
+        for _ in 0..4 {
+            let v = big_values.read_value::<::half::bf16>().expect("unexpected error");
+
+            assert_eq!(v.to_f32().to_bits(),
+                       big_floats.read_bf16().expect("unexpected error").to_bits());
+
+            let v = little_values.read_value::<::half::bf16>().expect("unexpected error");
+
+            assert_eq!(v.to_f32().to_bits(),
+                       little_floats.read_bf16().expect("unexpected error").to_bits());
+        }
+
+        let mut writer = big::Write::new(Buffer::new(Vec::new()));
+
+        writer.write_value(&::half::f16::from_f32(1.0)).expect("unexpected error");
+        writer.write_value(&::half::bf16::from_f32(-2.0)).expect("unexpected error");
+        assert_eq!(**writer, vec![0x3C, 0x00, 0xC0, 0x00]);
+
+        let mut writer = little::Write::new(Buffer::new(Vec::new()));
+
+        writer.write_value(&::half::f16::from_f32(1.0)).expect("unexpected error");
+        writer.write_value(&::half::bf16::from_f32(-2.0)).expect("unexpected error");
+        assert_eq!(**writer, vec![0x00, 0x3C, 0x00, 0xC0]);
+    }
+}
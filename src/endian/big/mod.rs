@@ -2,6 +2,7 @@ use std::io;
 use std::ops;
 use super::EndianRead;
 use super::EndianWrite;
+use super::super::position::Position;
 
 /// The Read struct wraps a type that implements the io::Read trait to provide big endian reading functions, satisfying the EndianRead trait.
 pub struct Read<T: io::Read> {
@@ -15,6 +16,25 @@ impl<T: io::Read> Read<T> {
     }
 }
 
+impl<T: io::Read + io::Seek> Read<T> {
+    /// Skips `n` bytes by seeking forward in the wrapped io::Seek, rather than reading and
+    /// discarding them as `EndianRead::skip` does.
+    ///
+    /// As with `EndianRead::skip`, if fewer than `n` bytes remain an UnexpectedEof error is
+    /// returned, and the reader is left at its end.
+    pub fn skip(&mut self, n: u64) -> io::Result<()> {
+        let start = self.reader.stream_position()?;
+        let end = self.reader.seek(io::SeekFrom::End(0))?;
+        match start.checked_add(n) {
+            Some(target) if target <= end => {
+                self.reader.seek(io::SeekFrom::Start(target))?;
+                Ok(())
+            }
+            _ => Err(io::Error::new(io::ErrorKind::UnexpectedEof, "failed to skip whole length")),
+        }
+    }
+}
+
 impl<T: io::Read> io::Read for Read<T> {
     /// The read function forwards the call to the embedded io::Read.
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
@@ -57,6 +77,20 @@ impl<T: io::Read> EndianRead for Read<T> {
     }
 }
 
+impl<T: io::Read + io::Seek> io::Seek for Read<T> {
+    /// The seek function forwards the call to the embedded io::Seek.
+    fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+        self.reader.seek(pos)
+    }
+}
+
+impl<T: io::Read + Position> Position for Read<T> {
+    /// The position function forwards the call to the embedded io::Read.
+    fn position(&self) -> u64 {
+        self.reader.position()
+    }
+}
+
 impl<T: io::Read> ops::Deref for Read<T> {
     type Target = T;
 
@@ -115,6 +149,20 @@ impl<T: io::Write> EndianWrite for Write<T> {
     }
 }
 
+impl<T: io::Write + io::Seek> io::Seek for Write<T> {
+    /// The seek function forwards the call to the embedded io::Seek.
+    fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+        self.writer.seek(pos)
+    }
+}
+
+impl<T: io::Write + Position> Position for Write<T> {
+    /// The position function forwards the call to the embedded io::Write.
+    fn position(&self) -> u64 {
+        self.writer.position()
+    }
+}
+
 impl<T: io::Write> ops::Deref for Write<T> {
     type Target = T;
 
@@ -126,9 +174,15 @@ impl<T: io::Write> ops::Deref for Write<T> {
 
 #[cfg(test)]
 mod tests {
+    use std::io;
+    use std::io::Write;
     use super::super::super::buffer::Buffer;
     use super::super::EndianRead;
     use super::super::EndianWrite;
+    use super::super::FloatCheck;
+    use super::super::super::fixed::F2Dot14;
+    use super::super::super::fixed::Q16_16;
+    use super::super::super::fixed::Q8_8;
 
     #[test]
     fn read() {
@@ -153,5 +207,186 @@ mod tests {
         assert_eq!(**buffer,
                    vec![10, 11, 12, 13, 12, 13, 1, 2, 3, 4, 5, 6, 7, 8, 65]);
     }
+    #[test]
+    fn utf16() {
+        let mut buffer = super::Read::new(Buffer::new(vec![0, 72, 0, 105, 216, 61, 222, 0, 0, 0,
+                                                           216, 61, 0, 33]));
+
+        assert_eq!(buffer.read_utf16_nul().expect("unexpected error"),
+                   "Hi\u{1F600}");
+        assert!(buffer.read_utf16(2).is_err());
 
+        let mut buffer = super::Read::new(Buffer::new(vec![0, 72, 0, 105, 216, 61, 222, 0, 0, 0,
+                                                           216, 61, 0, 33]));
+
+        assert_eq!(buffer.read_utf16(4).expect("unexpected error"), "Hi\u{1F600}");
+        buffer.read_u16().expect("unexpected error");
+        assert_eq!(buffer.read_utf16_lossy(2).expect("unexpected error"),
+                   "\u{FFFD}!");
+
+        let mut buffer = super::Write::new(Buffer::new(Vec::new()));
+
+        buffer.write_utf16_nul("Hi\u{1F600}").expect("unexpected error");
+        assert_eq!(**buffer, vec![0, 72, 0, 105, 216, 61, 222, 0, 0, 0]);
+    }
+    #[test]
+    fn half() {
+        let mut buffer = super::Write::new(Buffer::new(Vec::new()));
+
+        buffer.write_f16(1.0).expect("unexpected error");
+        buffer.write_f16(-2.0).expect("unexpected error");
+        buffer.write_f16(65504.0).expect("unexpected error");
+        buffer.write_f16(5.960_464_5e-8).expect("unexpected error");
+        buffer.write_f16(1e6).expect("unexpected error");
+        buffer.write_bf16(1.0).expect("unexpected error");
+        buffer.write_bf16(-2.0).expect("unexpected error");
+        buffer.write_bf16(1.0 / 3.0).expect("unexpected error");
+        assert_eq!(**buffer,
+                   vec![60, 0, 192, 0, 123, 255, 0, 1, 124, 0,
+                        63, 128, 192, 0, 62, 171]);
+
+        let mut buffer = super::Read::new(Buffer::new((**buffer).clone()));
+
+        assert_eq!(buffer.read_f16().expect("unexpected error"), 1.0);
+        assert_eq!(buffer.read_f16().expect("unexpected error"), -2.0);
+        assert_eq!(buffer.read_f16().expect("unexpected error"), 65504.0);
+        assert_eq!(buffer.read_f16().expect("unexpected error"),
+                   5.960_464_5e-8);
+        assert_eq!(buffer.read_f16().expect("unexpected error"),
+                   f32::INFINITY);
+        assert_eq!(buffer.read_bf16().expect("unexpected error"), 1.0);
+        assert_eq!(buffer.read_bf16().expect("unexpected error"), -2.0);
+        assert_eq!(buffer.read_bf16().expect("unexpected error"), 0.333_984_38);
+    }
+    #[test]
+    fn fixed() {
+        let mut buffer = super::Read::new(Buffer::new(vec![0, 1, 128, 0, 255, 128, 64, 0]));
+
+        assert_eq!(buffer.read_q16_16().expect("unexpected error").to_f64(), 1.5);
+        assert_eq!(buffer.read_q8_8().expect("unexpected error").to_f64(), -0.5);
+        assert_eq!(buffer.read_f2dot14().expect("unexpected error").to_f64(), 1.0);
+
+        let mut buffer = super::Write::new(Buffer::new(Vec::new()));
+
+        buffer.write_q16_16(Q16_16::from_f64(1.5)).expect("unexpected error");
+        buffer.write_q8_8(Q8_8::from_f64(-0.5)).expect("unexpected error");
+        buffer.write_f2dot14(F2Dot14::from_f64(1.0)).expect("unexpected error");
+        assert_eq!(**buffer, vec![0, 1, 128, 0, 255, 128, 64, 0]);
+    }
+    #[test]
+    fn checked_float() {
+        let mut buffer = super::Read::new(Buffer::new(vec![255, 192, 0, 1, 255, 128, 0, 0, 255,
+                                                           192, 0, 1, 127, 240, 0, 0, 0, 0, 0, 0,
+                                                           63, 128, 0, 0]));
+
+        assert_eq!(buffer.read_f32_checked(FloatCheck::CanonicalNan)
+                       .expect("unexpected error")
+                       .to_bits(),
+                   0x7fc00000);
+        assert_eq!(buffer.read_f32_checked(FloatCheck::RejectNan).expect("unexpected error"),
+                   f32::NEG_INFINITY);
+        assert_eq!(buffer.read_f32_checked(FloatCheck::RejectNan)
+                       .expect_err("expected error")
+                       .kind(),
+                   io::ErrorKind::InvalidData);
+        assert!(buffer.read_f64_checked(FloatCheck::Finite).is_err());
+        assert_eq!(buffer.read_f32_checked(FloatCheck::Finite).expect("unexpected error"),
+                   1.0);
+
+        let mut buffer = super::Write::new(Buffer::new(Vec::new()));
+
+        buffer.write_f64_checked(-f64::NAN, FloatCheck::CanonicalNan).expect("unexpected error");
+        assert!(buffer.write_f64_checked(f64::NAN, FloatCheck::RejectNan).is_err());
+        assert!(buffer.write_f32_checked(f32::INFINITY, FloatCheck::Finite).is_err());
+        buffer.write_f32_checked(f32::INFINITY, FloatCheck::Raw).expect("unexpected error");
+        assert_eq!(**buffer, vec![127, 248, 0, 0, 0, 0, 0, 0, 127, 128, 0, 0]);
+    }
+    #[test]
+    fn expect() {
+        let mut buffer = super::Read::new(Buffer::new(vec![137, 80, 78, 71, 202, 254, 186, 190,
+                                                           0, 1, 137, 81, 78, 71, 202, 254]));
+
+        buffer.expect_bytes(b"\x89PNG").expect("unexpected error");
+        buffer.expect_u32(0xCAFEBABE).expect("unexpected error");
+        buffer.expect_u16(1).expect("unexpected error");
+
+        let err = buffer.expect_bytes(b"\x89PNG").expect_err("expected error");
+
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(err.to_string(),
+                   "unexpected value: expected b\"\\x89PNG\", found b\"\\x89QNG\"");
+
+        let err = buffer.expect_u16(0xCAFF).expect_err("expected error");
+
+        assert_eq!(err.to_string(), "unexpected value: expected 0xcaff, found 0xcafe");
+    }
+    #[test]
+    fn skip() {
+        let mut buffer = super::Read::new(Buffer::new(vec![1, 2, 3, 4, 0, 5, 6, 7, 8]));
+
+        EndianRead::skip(&mut buffer, 2).expect("unexpected error");
+        assert_eq!(buffer.read_u8().expect("unexpected error"), 3);
+        assert_eq!(buffer.skip_until(0).expect("unexpected error"), 2);
+        assert_eq!(buffer.read_u8().expect("unexpected error"), 5);
+        assert!(EndianRead::skip(&mut buffer, 4).is_err());
+
+        let mut cursor = super::Read::new(io::Cursor::new(vec![1, 2, 3, 4, 5, 6]));
+
+        cursor.skip(4).expect("unexpected error");
+        assert_eq!(cursor.position(), 4);
+        assert_eq!(cursor.read_u16().expect("unexpected error"), 0x0506);
+        assert_eq!(cursor.skip_until(0).expect("unexpected error"), 0);
+
+        let mut cursor = super::Read::new(io::Cursor::new(vec![1, 2, 3]));
+
+        cursor.skip(1).expect("unexpected error");
+        assert_eq!(cursor.skip(3).expect_err("expected error").kind(),
+                   io::ErrorKind::UnexpectedEof);
+        assert_eq!(cursor.position(), 3);
+    }
+    struct AppendOnly(io::Cursor<Vec<u8>>);
+
+    impl io::Write for AppendOnly {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            if self.0.position() < self.0.get_ref().len() as u64 {
+                return Err(io::Error::new(io::ErrorKind::PermissionDenied, "append only"));
+            }
+            self.0.write(buf)
+        }
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl io::Seek for AppendOnly {
+        fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+            self.0.seek(pos)
+        }
+    }
+
+    #[test]
+    fn patch() {
+        let mut buffer = super::Write::new(io::Cursor::new(Vec::new()));
+        let length = buffer.reserve_u32().expect("unexpected error");
+
+        buffer.write_all(&[1, 2, 3]).expect("unexpected error");
+
+        let total = buffer.reserve_u16().expect("unexpected error");
+        let len = total.position() - length.end();
+
+        buffer.patch_u32(length, len as u32).expect("unexpected error");
+        assert!(buffer.patch_u32(total, 5).is_err());
+        buffer.patch_u16(total, 5).expect("unexpected error");
+        assert_eq!(*buffer.get_ref(), vec![0, 0, 0, 3, 1, 2, 3, 0, 5]);
+
+        let mut buffer = super::Write::new(AppendOnly(io::Cursor::new(Vec::new())));
+        let length = buffer.reserve_u16().expect("unexpected error");
+
+        buffer.write_u8(1).expect("unexpected error");
+        assert_eq!(buffer.patch_u16(length, 1).expect_err("expected error").kind(),
+                   io::ErrorKind::PermissionDenied);
+        assert_eq!(buffer.0.position(), 3);
+        buffer.write_u8(2).expect("unexpected error");
+        assert_eq!(*buffer.0.get_ref(), vec![0, 0, 1, 2]);
+    }
 }
@@ -0,0 +1,167 @@
+use std::io;
+use super::EndianWrite;
+use super::super::codec::Encode;
+
+/// The LengthPrefix trait is implemented by the unsigned integer types that can be used as the
+/// length prefix for `EndianWrite::write_length_prefixed`.
+pub trait LengthPrefix: Encode + Sized {
+    /// Converts a length into the prefix type, returning None if it does not fit.
+    fn from_length(len: u64) -> Option<Self>;
+}
+
+impl LengthPrefix for u8 {
+    fn from_length(len: u64) -> Option<u8> {
+        if len > u8::MAX as u64 {
+            None
+        } else {
+            Some(len as u8)
+        }
+    }
+}
+
+impl LengthPrefix for u16 {
+    fn from_length(len: u64) -> Option<u16> {
+        if len > u16::MAX as u64 {
+            None
+        } else {
+            Some(len as u16)
+        }
+    }
+}
+
+impl LengthPrefix for u32 {
+    fn from_length(len: u64) -> Option<u32> {
+        if len > u32::MAX as u64 {
+            None
+        } else {
+            Some(len as u32)
+        }
+    }
+}
+
+impl LengthPrefix for u64 {
+    fn from_length(len: u64) -> Option<u64> {
+        Some(len)
+    }
+}
+
+enum Segment {
+    Bytes(Vec<u8>),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+}
+
+/// The Deferred struct records writes in memory without committing to a byte order, so that
+/// they can later be replayed into any EndianWrite, which then determines the byte order.
+///
+/// This is what allows `EndianWrite::write_length_prefixed` to measure nested writes before
+/// writing them, without needing a seekable writer.
+#[derive(Default)]
+pub struct Deferred {
+    segments: Vec<Segment>,
+    len: u64,
+}
+
+impl Deferred {
+    /// Constructs a new, empty, `Deferred`.
+    pub fn new() -> Deferred {
+        Deferred::default()
+    }
+    /// Returns the number of bytes that have been written.
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+    /// Returns true if nothing has been written.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    /// Writes all of the recorded data to the given EndianWrite, in its byte order.
+    pub fn replay<W: EndianWrite + ?Sized>(&self, w: &mut W) -> io::Result<()> {
+        for segment in &self.segments {
+            match *segment {
+                Segment::Bytes(ref b) => w.write_all(b)?,
+                Segment::U16(v) => w.write_u16(v)?,
+                Segment::U32(v) => w.write_u32(v)?,
+                Segment::U64(v) => w.write_u64(v)?,
+            }
+        }
+        Ok(())
+    }
+    fn push(&mut self, segment: Segment, len: u64) {
+        self.segments.push(segment);
+        self.len += len;
+    }
+}
+
+impl io::Write for Deferred {
+    /// The write function records the given bytes.
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if let Some(&mut Segment::Bytes(ref mut b)) = self.segments.last_mut() {
+            b.extend_from_slice(buf);
+            self.len += buf.len() as u64;
+            return Ok(buf.len());
+        }
+        self.push(Segment::Bytes(buf.to_vec()), buf.len() as u64);
+        Ok(buf.len())
+    }
+    /// The flush function is a no-op, always returning Ok(()).
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl EndianWrite for Deferred {
+    /// Record a single unsigned 16-bit integer, to be written in the byte order of the replay.
+    fn write_u16(&mut self, v: u16) -> io::Result<()> {
+        self.push(Segment::U16(v), 2);
+        Ok(())
+    }
+    /// Record a single unsigned 32-bit integer, to be written in the byte order of the replay.
+    fn write_u32(&mut self, v: u32) -> io::Result<()> {
+        self.push(Segment::U32(v), 4);
+        Ok(())
+    }
+    /// Record a single unsigned 64-bit integer, to be written in the byte order of the replay.
+    fn write_u64(&mut self, v: u64) -> io::Result<()> {
+        self.push(Segment::U64(v), 8);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use super::super::super::buffer::Buffer;
+    use super::super::big;
+    use super::super::little;
+    use super::super::EndianWrite;
+
+    #[test]
+    fn length_prefixed_test() {
+        let mut writer = big::Write::new(Buffer::new(Vec::new()));
+
+        writer.write_length_prefixed::<u32>(|w| {
+                  w.write_all(b"RIFF")?;
+                  w.write_u16(0x0102)?;
+                  w.write_length_prefixed::<u8>(|w| w.write_u32(0x03040506))
+              })
+              .expect("unexpected error");
+        assert_eq!(**writer,
+                   vec![0, 0, 0, 11, b'R', b'I', b'F', b'F', 1, 2, 4, 3, 4, 5, 6]);
+
+        let mut writer = little::Write::new(Buffer::new(Vec::new()));
+
+        writer.write_length_prefixed::<u16>(|w| w.write_u32(0x03040506))
+              .expect("unexpected error");
+        assert_eq!(**writer, vec![4, 0, 6, 5, 4, 3]);
+    }
+
+    #[test]
+    fn too_long_test() {
+        let mut writer = big::Write::new(Buffer::new(Vec::new()));
+
+        assert!(writer.write_length_prefixed::<u8>(|w| w.write_all(&[0; 256])).is_err());
+        assert!(writer.is_empty());
+    }
+}
@@ -1,8 +1,57 @@
+use std::ascii;
+use std::char;
+use std::fmt;
 use std::io;
-use std::mem;
+use codec::Decode;
+use codec::Encode;
+use codec::MAX_PREALLOCATE;
+use error::ParseError;
+use fixed::F2Dot14;
+use fixed::Q16_16;
+use fixed::Q8_8;
+use position::Position;
+use self::deferred::Deferred;
+use self::deferred::LengthPrefix;
+use self::sub::SubReader;
 
 pub mod little;
 pub mod big;
+pub mod sub;
+pub mod deferred;
+
+/// The FloatCheck enum determines how the checked float reading and writing functions handle
+/// NaN and infinite values.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FloatCheck {
+    /// Values are passed through unchanged, as with `read_f32` and `write_f32`.
+    Raw,
+    /// Any NaN is replaced with the canonical quiet NaN, discarding its sign and payload.
+    CanonicalNan,
+    /// A NaN results in an InvalidData error.
+    RejectNan,
+    /// A NaN or an infinity results in an InvalidData error.
+    Finite,
+}
+
+/// The Placeholder struct records the location of a value reserved in a seekable writer, so
+/// that it can be filled in later, once it is known.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Placeholder {
+    position: u64,
+    size: u64,
+}
+
+impl Placeholder {
+    /// Returns the position of the start of the reserved value.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+    /// Returns the position immediately after the reserved value, which is the start of any
+    /// data written after it.
+    pub fn end(&self) -> u64 {
+        self.position + self.size
+    }
+}
 
 /// The EndianRead trait extends the io::Read trait to include readers for various number types.
 pub trait EndianRead: io::Read {
@@ -51,17 +100,223 @@ pub trait EndianRead: io::Read {
     /// Read a single 32-bit floating point number from the reader.
     fn read_f32(&mut self) -> io::Result<f32> {
         match self.read_u32() {
-            Ok(v) => Ok(unsafe { mem::transmute(v) }),
+            Ok(v) => Ok(f32::from_bits(v)),
             Err(e) => Err(e),
         }
     }
     /// Read a single 64-bit floating point number from the reader.
     fn read_f64(&mut self) -> io::Result<f64> {
         match self.read_u64() {
-            Ok(v) => Ok(unsafe { mem::transmute(v) }),
+            Ok(v) => Ok(f64::from_bits(v)),
+            Err(e) => Err(e),
+        }
+    }
+    /// Read a single 32-bit floating point number from the reader, handling NaN and infinite
+    /// values according to the given FloatCheck.
+    fn read_f32_checked(&mut self, check: FloatCheck) -> io::Result<f32> {
+        match self.read_f32() {
+            Ok(v) => check_f32(v, check),
+            Err(e) => Err(e),
+        }
+    }
+    /// Read a single 64-bit floating point number from the reader, handling NaN and infinite
+    /// values according to the given FloatCheck.
+    fn read_f64_checked(&mut self, check: FloatCheck) -> io::Result<f64> {
+        match self.read_f64() {
+            Ok(v) => check_f64(v, check),
+            Err(e) => Err(e),
+        }
+    }
+    /// Read a single 16-bit IEEE 754 half precision floating point number from the reader,
+    /// converting it to a 32-bit floating point number.
+    fn read_f16(&mut self) -> io::Result<f32> {
+        match self.read_u16() {
+            Ok(v) => Ok(f16_to_f32(v)),
+            Err(e) => Err(e),
+        }
+    }
+    /// Read a single 16-bit bfloat16 floating point number from the reader, converting it to a
+    /// 32-bit floating point number.
+    fn read_bf16(&mut self) -> io::Result<f32> {
+        match self.read_u16() {
+            Ok(v) => Ok(f32::from_bits((v as u32) << 16)),
             Err(e) => Err(e),
         }
     }
+    /// Read a single signed 16.16 fixed-point number from the reader.
+    fn read_q16_16(&mut self) -> io::Result<Q16_16> {
+        match self.read_i32() {
+            Ok(v) => Ok(Q16_16::from_bits(v)),
+            Err(e) => Err(e),
+        }
+    }
+    /// Read a single signed 8.8 fixed-point number from the reader.
+    fn read_q8_8(&mut self) -> io::Result<Q8_8> {
+        match self.read_i16() {
+            Ok(v) => Ok(Q8_8::from_bits(v)),
+            Err(e) => Err(e),
+        }
+    }
+    /// Read a single signed 2.14 fixed-point number from the reader.
+    fn read_f2dot14(&mut self) -> io::Result<F2Dot14> {
+        match self.read_i16() {
+            Ok(v) => Ok(F2Dot14::from_bits(v)),
+            Err(e) => Err(e),
+        }
+    }
+    /// Read `n` UTF-16 code units from the reader, in the byte order of the reader, and decode
+    /// them into a String.
+    ///
+    /// An unpaired surrogate results in an InvalidData error.
+    fn read_utf16(&mut self, n: usize) -> io::Result<String> {
+        let mut units = Vec::with_capacity(n.min(MAX_PREALLOCATE));
+        for _ in 0..n {
+            units.push(self.read_u16()?);
+        }
+        decode_utf16(&units)
+    }
+    /// Read `n` UTF-16 code units from the reader, replacing any unpaired surrogates with
+    /// U+FFFD.
+    fn read_utf16_lossy(&mut self, n: usize) -> io::Result<String> {
+        let mut units = Vec::with_capacity(n.min(MAX_PREALLOCATE));
+        for _ in 0..n {
+            units.push(self.read_u16()?);
+        }
+        Ok(String::from_utf16_lossy(&units))
+    }
+    /// Read UTF-16 code units from the reader up to, and consuming, a NUL terminator.
+    ///
+    /// An unpaired surrogate results in an InvalidData error.
+    fn read_utf16_nul(&mut self) -> io::Result<String> {
+        let mut units = Vec::new();
+        loop {
+            match self.read_u16()? {
+                0 => return decode_utf16(&units),
+                u => units.push(u),
+            }
+        }
+    }
+    /// Read UTF-16 code units from the reader up to, and consuming, a NUL terminator,
+    /// replacing any unpaired surrogates with U+FFFD.
+    fn read_utf16_nul_lossy(&mut self) -> io::Result<String> {
+        let mut units = Vec::new();
+        loop {
+            match self.read_u16()? {
+                0 => return Ok(String::from_utf16_lossy(&units)),
+                u => units.push(u),
+            }
+        }
+    }
+    /// Read a value of any type that implements the Decode trait from the reader.
+    fn read_value<T: Decode>(&mut self) -> io::Result<T>
+        where Self: Sized
+    {
+        T::decode(self)
+    }
+    /// Read and discard `n` bytes from the reader, returning an UnexpectedEof error if the
+    /// reader ends first.
+    ///
+    /// The big and little endian wrappers shadow this with a function that seeks instead, when
+    /// the wrapped reader implements io::Seek.
+    fn skip(&mut self, n: u64) -> io::Result<()> {
+        let mut buffer = [0; 4096];
+        let mut remaining = n;
+        while remaining > 0 {
+            let max = if remaining < buffer.len() as u64 {
+                remaining as usize
+            } else {
+                buffer.len()
+            };
+            match self.read(&mut buffer[..max]) {
+                Ok(0) => {
+                    return Err(io::Error::new(io::ErrorKind::UnexpectedEof,
+                                              "failed to skip whole length"))
+                }
+                Ok(c) => remaining -= c as u64,
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+    /// Read and discard bytes from the reader up to, and including, the given byte, returning
+    /// the number of bytes discarded. Reaching the end of the reader is not an error.
+    fn skip_until(&mut self, byte: u8) -> io::Result<usize> {
+        let mut count = 0;
+        loop {
+            match self.read_u8() {
+                Ok(b) => {
+                    count += 1;
+                    if b == byte {
+                        return Ok(count);
+                    }
+                }
+                Err(ref e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(count),
+                Err(e) => return Err(e),
+            }
+        }
+    }
+    /// Skip padding bytes until the position of the reader is a multiple of `n`.
+    fn align_to(&mut self, n: u64) -> io::Result<()>
+        where Self: Position
+    {
+        let padding = padding(self.position(), n)?;
+        self.skip(padding)
+    }
+    /// Skip padding bytes until the position of the reader is a multiple of `n`, returning an
+    /// InvalidData error if any of them are not zero.
+    fn align_to_zeroed(&mut self, n: u64) -> io::Result<()>
+        where Self: Position
+    {
+        for _ in 0..padding(self.position(), n)? {
+            let found = self.read_u8()?;
+            if found != 0 {
+                return Err(ParseError::mismatch(Hex(0), Hex(found)).into());
+            }
+        }
+        Ok(())
+    }
+    /// Returns a SubReader that reads at most `len` bytes from this reader, in the same byte
+    /// order.
+    fn sub_reader(&mut self, len: u64) -> SubReader<'_, Self>
+        where Self: Sized
+    {
+        SubReader::new(self, len)
+    }
+    /// Read bytes from the reader, returning an InvalidData error describing what was found if
+    /// they do not match the expected bytes.
+    fn expect_bytes(&mut self, expected: &[u8]) -> io::Result<()> {
+        let mut found = vec![0; expected.len()];
+        self.read_exact(&mut found)?;
+        if found != expected {
+            return Err(ParseError::mismatch(ByteStr(expected), ByteStr(&found)).into());
+        }
+        Ok(())
+    }
+    /// Read a single unsigned 8-bit integer from the reader, returning an InvalidData error if
+    /// it does not match the expected value.
+    fn expect_u8(&mut self, expected: u8) -> io::Result<()> {
+        let found = self.read_u8()?;
+        expect(expected, found)
+    }
+    /// Read a single unsigned 16-bit integer from the reader, returning an InvalidData error if
+    /// it does not match the expected value.
+    fn expect_u16(&mut self, expected: u16) -> io::Result<()> {
+        let found = self.read_u16()?;
+        expect(expected, found)
+    }
+    /// Read a single unsigned 32-bit integer from the reader, returning an InvalidData error if
+    /// it does not match the expected value.
+    fn expect_u32(&mut self, expected: u32) -> io::Result<()> {
+        let found = self.read_u32()?;
+        expect(expected, found)
+    }
+    /// Read a single unsigned 64-bit integer from the reader, returning an InvalidData error if
+    /// it does not match the expected value.
+    fn expect_u64(&mut self, expected: u64) -> io::Result<()> {
+        let found = self.read_u64()?;
+        expect(expected, found)
+    }
 }
 
 /// The EndianWrite trait extends the io::Write trait to include writes for various number types.
@@ -95,10 +350,302 @@ pub trait EndianWrite: io::Write {
     }
     /// Write a single 8-bit floating point number to the writer
     fn write_f32(&mut self, v: f32) -> io::Result<()> {
-        self.write_u32(unsafe { mem::transmute(v) })
+        self.write_u32(v.to_bits())
     }
     /// Write a single 32-bit floating point number to the writer
     fn write_f64(&mut self, v: f64) -> io::Result<()> {
-        self.write_u64(unsafe { mem::transmute(v) })
+        self.write_u64(v.to_bits())
+    }
+    /// Write a single 32-bit floating point number to the writer, handling NaN and infinite
+    /// values according to the given FloatCheck.
+    fn write_f32_checked(&mut self, v: f32, check: FloatCheck) -> io::Result<()> {
+        let v = check_f32(v, check)?;
+        self.write_f32(v)
+    }
+    /// Write a single 64-bit floating point number to the writer, handling NaN and infinite
+    /// values according to the given FloatCheck.
+    fn write_f64_checked(&mut self, v: f64, check: FloatCheck) -> io::Result<()> {
+        let v = check_f64(v, check)?;
+        self.write_f64(v)
+    }
+    /// Write a 32-bit floating point number to the writer as a 16-bit IEEE 754 half precision
+    /// floating point number, rounding to the nearest representable value.
+    fn write_f16(&mut self, v: f32) -> io::Result<()> {
+        self.write_u16(f32_to_f16(v))
+    }
+    /// Write a 32-bit floating point number to the writer as a 16-bit bfloat16 floating point
+    /// number, rounding to the nearest representable value.
+    fn write_bf16(&mut self, v: f32) -> io::Result<()> {
+        self.write_u16(f32_to_bf16(v))
+    }
+    /// Write a single signed 16.16 fixed-point number to the writer
+    fn write_q16_16(&mut self, v: Q16_16) -> io::Result<()> {
+        self.write_i32(v.to_bits())
+    }
+    /// Write a single signed 8.8 fixed-point number to the writer
+    fn write_q8_8(&mut self, v: Q8_8) -> io::Result<()> {
+        self.write_i16(v.to_bits())
+    }
+    /// Write a single signed 2.14 fixed-point number to the writer
+    fn write_f2dot14(&mut self, v: F2Dot14) -> io::Result<()> {
+        self.write_i16(v.to_bits())
+    }
+    /// Write the string to the writer as UTF-16 code units, in the byte order of the writer.
+    fn write_utf16(&mut self, s: &str) -> io::Result<()> {
+        for u in s.encode_utf16() {
+            self.write_u16(u)?;
+        }
+        Ok(())
+    }
+    /// Write the string to the writer as UTF-16 code units, followed by a NUL terminator.
+    fn write_utf16_nul(&mut self, s: &str) -> io::Result<()> {
+        self.write_utf16(s)?;
+        self.write_u16(0)
+    }
+    /// Write copies of the `fill` byte until the position of the writer is a multiple of `n`.
+    fn align_to(&mut self, n: u64, fill: u8) -> io::Result<()>
+        where Self: Position
+    {
+        for _ in 0..padding(self.position(), n)? {
+            self.write_u8(fill)?;
+        }
+        Ok(())
+    }
+    /// Reserve space for an unsigned 16-bit integer, writing zero in its place, to be filled in
+    /// later with `patch_u16`.
+    fn reserve_u16(&mut self) -> io::Result<Placeholder>
+        where Self: io::Seek
+    {
+        let position = self.stream_position()?;
+        self.write_u16(0)?;
+        Ok(Placeholder { position, size: 2 })
+    }
+    /// Reserve space for an unsigned 32-bit integer, writing zero in its place, to be filled in
+    /// later with `patch_u32`.
+    fn reserve_u32(&mut self) -> io::Result<Placeholder>
+        where Self: io::Seek
+    {
+        let position = self.stream_position()?;
+        self.write_u32(0)?;
+        Ok(Placeholder { position, size: 4 })
+    }
+    /// Reserve space for an unsigned 64-bit integer, writing zero in its place, to be filled in
+    /// later with `patch_u64`.
+    fn reserve_u64(&mut self) -> io::Result<Placeholder>
+        where Self: io::Seek
+    {
+        let position = self.stream_position()?;
+        self.write_u64(0)?;
+        Ok(Placeholder { position, size: 8 })
+    }
+    /// Fill in a value reserved with `reserve_u16`, returning to the current position afterwards.
+    fn patch_u16(&mut self, p: Placeholder, v: u16) -> io::Result<()>
+        where Self: io::Seek
+    {
+        let end = seek_placeholder(self, p, 2)?;
+        let result = self.write_u16(v);
+        self.seek(io::SeekFrom::Start(end))?;
+        result
+    }
+    /// Fill in a value reserved with `reserve_u32`, returning to the current position afterwards.
+    fn patch_u32(&mut self, p: Placeholder, v: u32) -> io::Result<()>
+        where Self: io::Seek
+    {
+        let end = seek_placeholder(self, p, 4)?;
+        let result = self.write_u32(v);
+        self.seek(io::SeekFrom::Start(end))?;
+        result
+    }
+    /// Fill in a value reserved with `reserve_u64`, returning to the current position afterwards.
+    fn patch_u64(&mut self, p: Placeholder, v: u64) -> io::Result<()>
+        where Self: io::Seek
+    {
+        let end = seek_placeholder(self, p, 8)?;
+        let result = self.write_u64(v);
+        self.seek(io::SeekFrom::Start(end))?;
+        result
+    }
+    /// Write a length prefix of type L, followed by everything written to the Deferred writer
+    /// by the given function, with the prefix being the number of bytes it wrote.
+    ///
+    /// The nested writes are buffered, and both they and the prefix are written in the byte
+    /// order of this writer. Nothing is written if the function returns an error, or if the
+    /// length does not fit in the prefix type.
+    fn write_length_prefixed<L: LengthPrefix>(&mut self,
+                                              f: impl FnOnce(&mut Deferred) -> io::Result<()>)
+                                              -> io::Result<()>
+        where Self: Sized
+    {
+        let mut nested = Deferred::new();
+        f(&mut nested)?;
+        match L::from_length(nested.len()) {
+            Some(l) => l.encode(self)?,
+            None => {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                          "length too large for prefix"))
+            }
+        }
+        nested.replay(self)
+    }
+    /// Write a value of any type that implements the Encode trait to the writer.
+    fn write_value<T: Encode + ?Sized>(&mut self, v: &T) -> io::Result<()>
+        where Self: Sized
+    {
+        v.encode(self)
+    }
+}
+
+/// Checks the size of the placeholder and seeks to it, returning the position to return to.
+fn seek_placeholder<S: io::Seek + ?Sized>(s: &mut S, p: Placeholder, size: u64) -> io::Result<u64> {
+    if p.size != size {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                  "placeholder reserved with a different size"));
+    }
+    let end = s.stream_position()?;
+    s.seek(io::SeekFrom::Start(p.position))?;
+    Ok(end)
+}
+
+/// Returns the number of bytes needed to advance the position to a multiple of `n`.
+fn padding(position: u64, n: u64) -> io::Result<u64> {
+    if n == 0 {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "alignment must be non-zero"));
+    }
+    Ok((n - position % n) % n)
+}
+
+fn expect<T: PartialEq + fmt::LowerHex>(expected: T, found: T) -> io::Result<()> {
+    if expected != found {
+        return Err(ParseError::mismatch(Hex(expected), Hex(found)).into());
+    }
+    Ok(())
+}
+
+/// Hex formats an integer in hexadecimal for error messages.
+struct Hex<T>(T);
+
+impl<T: fmt::LowerHex> fmt::Debug for Hex<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:#x}", self.0)
+    }
+}
+
+/// ByteStr formats a byte slice as an escaped byte string literal for error messages.
+struct ByteStr<'a>(&'a [u8]);
+
+impl<'a> fmt::Debug for ByteStr<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("b\"")?;
+        for b in self.0 {
+            write!(f, "{}", ascii::escape_default(*b))?;
+        }
+        f.write_str("\"")
+    }
+}
+
+fn check_f32(v: f32, check: FloatCheck) -> io::Result<f32> {
+    match check {
+        FloatCheck::CanonicalNan if v.is_nan() => Ok(f32::from_bits(0x7fc0_0000)),
+        FloatCheck::RejectNan | FloatCheck::Finite if v.is_nan() => Err(invalid_float(v)),
+        FloatCheck::Finite if v.is_infinite() => Err(invalid_float(v)),
+        _ => Ok(v),
+    }
+}
+
+fn check_f64(v: f64, check: FloatCheck) -> io::Result<f64> {
+    match check {
+        FloatCheck::CanonicalNan if v.is_nan() => Ok(f64::from_bits(0x7ff8_0000_0000_0000)),
+        FloatCheck::RejectNan | FloatCheck::Finite if v.is_nan() => Err(invalid_float(v)),
+        FloatCheck::Finite if v.is_infinite() => Err(invalid_float(v)),
+        _ => Ok(v),
+    }
+}
+
+fn invalid_float<T: fmt::Display>(v: T) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData,
+                   format!("invalid floating point value: {}", v))
+}
+
+/// Converts the bits of a half precision float to a single precision float.
+fn f16_to_f32(h: u16) -> f32 {
+    let sign = ((h & 0x8000) as u32) << 16;
+    let exp = ((h >> 10) & 0x1f) as u32;
+    let man = (h & 0x3ff) as u32;
+    let bits = match exp {
+        0 if man == 0 => sign,
+        0 => {
+            let mut e = 113;
+            let mut m = man;
+            while m & 0x400 == 0 {
+                m <<= 1;
+                e -= 1;
+            }
+            sign | e << 23 | (m & 0x3ff) << 13
+        }
+        0x1f => sign | 0x7f80_0000 | man << 13,
+        _ => sign | (exp + 112) << 23 | man << 13,
+    };
+    f32::from_bits(bits)
+}
+
+/// Converts a single precision float to the bits of a half precision float, rounding to nearest,
+/// ties to even.
+fn f32_to_f16(v: f32) -> u16 {
+    let x = v.to_bits();
+    let sign = ((x >> 16) & 0x8000) as u16;
+    let exp = ((x >> 23) & 0xff) as i32;
+    let man = x & 0x7f_ffff;
+    if exp == 0xff {
+        return if man == 0 {
+            sign | 0x7c00
+        } else {
+            sign | 0x7e00 | (man >> 13) as u16
+        };
+    }
+    let e = exp - 112;
+    if e >= 0x1f {
+        return sign | 0x7c00;
+    }
+    let (m, shift, base) = if e <= 0 {
+        if e < -10 {
+            return sign;
+        }
+        (man | 0x80_0000, (14 - e) as u32, 0)
+    } else {
+        (man, 13, (e as u16) << 10)
+    };
+    let half = 1 << (shift - 1);
+    let rem = m & ((1 << shift) - 1);
+    let mut h = base | (m >> shift) as u16;
+    if rem > half || (rem == half && h & 1 == 1) {
+        h += 1;
+    }
+    sign | h
+}
+
+/// Converts a single precision float to the bits of a bfloat16, rounding to nearest, ties to
+/// even.
+fn f32_to_bf16(v: f32) -> u16 {
+    let x = v.to_bits();
+    if v.is_nan() {
+        return (x >> 16) as u16 | 0x40;
+    }
+    ((x + 0x7fff + ((x >> 16) & 1)) >> 16) as u16
+}
+
+/// Decodes UTF-16 code units into a String, returning an InvalidData error on an unpaired
+/// surrogate.
+fn decode_utf16(units: &[u16]) -> io::Result<String> {
+    let mut s = String::with_capacity(units.len());
+    for c in char::decode_utf16(units.iter().cloned()) {
+        match c {
+            Ok(c) => s.push(c),
+            Err(e) => {
+                return Err(io::Error::new(io::ErrorKind::InvalidData,
+                                          format!("invalid UTF-16: unpaired surrogate {:#06x}",
+                                                  e.unpaired_surrogate())))
+            }
+        }
     }
+    Ok(s)
 }
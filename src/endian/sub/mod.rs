@@ -0,0 +1,178 @@
+use std::io;
+use super::EndianRead;
+use super::super::position::Position;
+
+/// The SubReader struct wraps an EndianRead, limiting reads to a fixed number of bytes while
+/// keeping the byte order of the wrapped reader.
+///
+/// This allows a length delimited section of a stream to be parsed without overrunning into
+/// whatever follows it.
+///
+/// If reading a value from the wrapped EndianRead fails, the number of bytes it consumed is
+/// unknown, so `skip_rest` then returns an error rather than skipping to the wrong offset.
+pub struct SubReader<'a, R: 'a + EndianRead> {
+    reader: &'a mut R,
+    remaining: u64,
+    poisoned: bool,
+}
+
+impl<'a, R: EndianRead> SubReader<'a, R> {
+    /// Constructs a new `SubReader`, limited to reading `len` bytes from the given EndianRead.
+    pub fn new(reader: &'a mut R, len: u64) -> SubReader<'a, R> {
+        SubReader {
+            reader,
+            remaining: len,
+            poisoned: false,
+        }
+    }
+    /// Returns the number of bytes that can still be read.
+    pub fn remaining(&self) -> u64 {
+        self.remaining
+    }
+    /// Reads and discards any unread bytes, leaving the wrapped reader positioned at the end of
+    /// the section.
+    ///
+    /// This returns an error if an earlier read of a value from the wrapped EndianRead failed.
+    pub fn skip_rest(&mut self) -> io::Result<()> {
+        if self.poisoned {
+            return Err(io::Error::other("section position lost after a failed read"));
+        }
+        let remaining = self.remaining;
+        let n = io::copy(&mut io::Read::take(&mut *self.reader, remaining), &mut io::sink())?;
+        self.remaining -= n;
+        if self.remaining > 0 {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "failed to skip section"));
+        }
+        Ok(())
+    }
+    fn check(&self, n: u64) -> io::Result<()> {
+        if self.remaining < n {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "end of section"));
+        }
+        Ok(())
+    }
+    fn consume<T>(&mut self, n: u64, result: io::Result<T>) -> io::Result<T> {
+        match result {
+            Ok(v) => {
+                self.remaining -= n;
+                Ok(v)
+            }
+            Err(e) => {
+                self.poisoned = true;
+                Err(e)
+            }
+        }
+    }
+}
+
+impl<'a, R: EndianRead> io::Read for SubReader<'a, R> {
+    /// The read function forwards the call to the wrapped EndianRead, limiting the length of
+    /// the read to the remaining bytes.
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.remaining == 0 {
+            return Ok(0);
+        }
+        let max = if (buf.len() as u64) < self.remaining {
+            buf.len()
+        } else {
+            self.remaining as usize
+        };
+        let n = self.reader.read(&mut buf[..max])?;
+        self.remaining -= n as u64;
+        Ok(n)
+    }
+}
+
+impl<'a, R: EndianRead> EndianRead for SubReader<'a, R> {
+    /// Read a single unsigned 16-bit integer from the wrapped EndianRead, in its byte order.
+    fn read_u16(&mut self) -> io::Result<u16> {
+        self.check(2)?;
+        let result = self.reader.read_u16();
+        self.consume(2, result)
+    }
+    /// Read a single unsigned 32-bit integer from the wrapped EndianRead, in its byte order.
+    fn read_u32(&mut self) -> io::Result<u32> {
+        self.check(4)?;
+        let result = self.reader.read_u32();
+        self.consume(4, result)
+    }
+    /// Read a single unsigned 64-bit integer from the wrapped EndianRead, in its byte order.
+    fn read_u64(&mut self) -> io::Result<u64> {
+        self.check(8)?;
+        let result = self.reader.read_u64();
+        self.consume(8, result)
+    }
+}
+
+impl<'a, R: EndianRead + Position> Position for SubReader<'a, R> {
+    /// The position function forwards the call to the wrapped EndianRead.
+    fn position(&self) -> u64 {
+        self.reader.position()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+    use super::super::super::buffer::Buffer;
+    use super::super::big;
+    use super::super::little;
+    use super::super::EndianRead;
+
+    #[test]
+    fn sub_reader_test() {
+        let mut reader = big::Read::new(Buffer::new(vec![0, 6, 1, 2, 3, 4, 5, 6, 7, 8]));
+        let len = reader.read_u16().expect("unexpected error") as u64;
+
+        {
+            let mut sub = reader.sub_reader(len);
+
+            assert_eq!(sub.read_u32().expect("unexpected error"), 0x01020304);
+            assert_eq!(sub.remaining(), 2);
+            assert_eq!(sub.read_u32().expect_err("expected error").kind(),
+                       io::ErrorKind::UnexpectedEof);
+            sub.skip_rest().expect("unexpected error");
+            assert_eq!(sub.remaining(), 0);
+            assert!(sub.read_u8().is_err());
+        }
+
+        assert_eq!(reader.read_u16().expect("unexpected error"), 0x0708);
+    }
+
+    #[test]
+    fn nested_test() {
+        let mut reader = little::Read::new(Buffer::new(vec![1, 2, 3, 4, 5]));
+        let mut outer = reader.sub_reader(4);
+
+        {
+            let mut inner = outer.sub_reader(2);
+
+            assert_eq!(inner.read_u16().expect("unexpected error"), 0x0201);
+        }
+
+        assert_eq!(outer.remaining(), 2);
+        assert_eq!(outer.read_u16().expect("unexpected error"), 0x0403);
+        assert!(outer.skip_rest().is_ok());
+    }
+
+    #[test]
+    fn failed_read_test() {
+        let mut reader = big::Read::new(Buffer::new(vec![1, 2]));
+        let mut sub = reader.sub_reader(8);
+
+        assert_eq!(sub.read_u32().expect_err("expected error").kind(),
+                   io::ErrorKind::UnexpectedEof);
+        assert_eq!(sub.remaining(), 8);
+        assert_eq!(sub.skip_rest().expect_err("expected error").kind(), io::ErrorKind::Other);
+    }
+
+    #[test]
+    fn short_test() {
+        let mut reader = big::Read::new(Buffer::new(vec![1, 2]));
+        let mut sub = reader.sub_reader(4);
+
+        assert_eq!(sub.skip_rest().expect_err("expected error").kind(),
+                   io::ErrorKind::UnexpectedEof);
+        assert_eq!(sub.remaining(), 2);
+    }
+}
@@ -0,0 +1,210 @@
+//! The error module provides ParseError, which adds a byte offset, a stack of context labels
+//! and expected vs found values to an error, while still travelling through io::Result.
+
+use std::error;
+use std::fmt;
+use std::io;
+use position::Position;
+
+/// The ParseError struct describes a failure to parse a stream, with optional details of where
+/// and why the failure happened.
+///
+/// A ParseError converts into an io::Error of the same kind, and can be recovered from it with
+/// `ParseError::from`, so details can be added as the error is returned through functions that
+/// use io::Result.
+#[derive(Debug)]
+pub struct ParseError {
+    kind: io::ErrorKind,
+    message: String,
+    offset: Option<u64>,
+    context: Vec<String>,
+    mismatch: Option<(String, String)>,
+    source: Option<io::Error>,
+}
+
+impl ParseError {
+    /// Constructs a new ParseError of the given kind, with the given message.
+    pub fn new<M: Into<String>>(kind: io::ErrorKind, message: M) -> ParseError {
+        ParseError {
+            kind,
+            message: message.into(),
+            offset: None,
+            context: Vec::new(),
+            mismatch: None,
+            source: None,
+        }
+    }
+    /// Constructs a new InvalidData ParseError, recording the expected and found values.
+    pub fn mismatch<E: fmt::Debug, F: fmt::Debug>(expected: E, found: F) -> ParseError {
+        let mut e = ParseError::new(io::ErrorKind::InvalidData, "unexpected value");
+        e.mismatch = Some((format!("{:?}", expected), format!("{:?}", found)));
+        e
+    }
+    /// Sets the byte offset at which the error occurred, if one has not already been set.
+    pub fn at(mut self, offset: u64) -> ParseError {
+        if self.offset.is_none() {
+            self.offset = Some(offset);
+        }
+        self
+    }
+    /// Adds a context label, such as a field or struct name, to the error. Labels should be
+    /// added from the innermost outwards.
+    pub fn context<L: Into<String>>(mut self, label: L) -> ParseError {
+        self.context.push(label.into());
+        self
+    }
+    /// Returns the kind of the error.
+    pub fn kind(&self) -> io::ErrorKind {
+        self.kind
+    }
+    /// Returns the byte offset at which the error occurred, if known.
+    pub fn offset(&self) -> Option<u64> {
+        self.offset
+    }
+    /// Returns the context labels of the error, from the outermost inwards.
+    pub fn context_labels(&self) -> Vec<&str> {
+        self.context.iter().rev().map(String::as_str).collect()
+    }
+    /// Returns the expected value, if the error was caused by a mismatch.
+    pub fn expected(&self) -> Option<&str> {
+        self.mismatch.as_ref().map(|m| m.0.as_str())
+    }
+    /// Returns the found value, if the error was caused by a mismatch.
+    pub fn found(&self) -> Option<&str> {
+        self.mismatch.as_ref().map(|m| m.1.as_str())
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.message)?;
+        if let Some((ref expected, ref found)) = self.mismatch {
+            write!(f, ": expected {}, found {}", expected, found)?;
+        }
+        if let Some(offset) = self.offset {
+            write!(f, " at offset {:#x}", offset)?;
+        }
+        if !self.context.is_empty() {
+            write!(f, " in {}", self.context_labels().join("."))?;
+        }
+        Ok(())
+    }
+}
+
+impl error::Error for ParseError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        self.source.as_ref().map(|e| e as &(dyn error::Error + 'static))
+    }
+}
+
+impl From<io::Error> for ParseError {
+    /// Converts an io::Error into a ParseError, recovering the original ParseError if the
+    /// io::Error was created from one.
+    fn from(e: io::Error) -> ParseError {
+        match e.downcast::<ParseError>() {
+            Ok(p) => p,
+            Err(e) => {
+                let mut p = ParseError::new(e.kind(), e.to_string());
+                p.source = Some(e);
+                p
+            }
+        }
+    }
+}
+
+impl From<ParseError> for io::Error {
+    fn from(e: ParseError) -> io::Error {
+        io::Error::new(e.kind, e)
+    }
+}
+
+/// The Context trait extends io::Result to allow details to be added to an error as it is
+/// returned.
+pub trait Context<T> {
+    /// Adds a context label to the error, if there is one.
+    fn context<L: Into<String>>(self, label: L) -> io::Result<T>;
+    /// Records the given offset as the offset of the error, if there is one and no offset has
+    /// already been recorded.
+    ///
+    /// Taking the position of a reader before reading a field, and passing it here, records the
+    /// start of the field that failed.
+    fn at_offset(self, offset: u64) -> io::Result<T>;
+    /// Records the current position of the given reader or writer as the offset of the error, if
+    /// there is one and no offset has already been recorded.
+    ///
+    /// This is the position after the failed read or write; use `at_offset` to record where it
+    /// started.
+    fn at<P: Position + ?Sized>(self, p: &P) -> io::Result<T>;
+}
+
+impl<T> Context<T> for io::Result<T> {
+    fn context<L: Into<String>>(self, label: L) -> io::Result<T> {
+        match self {
+            Ok(v) => Ok(v),
+            Err(e) => Err(ParseError::from(e).context(label).into()),
+        }
+    }
+    fn at_offset(self, offset: u64) -> io::Result<T> {
+        match self {
+            Ok(v) => Ok(v),
+            Err(e) => Err(ParseError::from(e).at(offset).into()),
+        }
+    }
+    fn at<P: Position + ?Sized>(self, p: &P) -> io::Result<T> {
+        self.at_offset(p.position())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+    use super::super::buffer::Buffer;
+    use super::super::endian::big;
+    use super::super::endian::EndianRead;
+    use super::super::position::Counting;
+    use super::super::position::Position;
+    use super::Context;
+    use super::ParseError;
+
+    fn read_tag<R: EndianRead>(r: &mut R) -> io::Result<u16> {
+        let tag = r.read_u16()?;
+        if tag != 0xABCD {
+            return Err(ParseError::mismatch(0xABCD, tag).into());
+        }
+        Ok(tag)
+    }
+
+    #[test]
+    fn context_test() {
+        let mut reader = big::Read::new(Counting::new(Buffer::new(vec![1, 2, 3, 4])));
+
+        reader.read_u16().expect("unexpected error");
+
+        let start = reader.position();
+        let err = read_tag(&mut reader).at_offset(start).context("tag").context("Header");
+
+        let err = ParseError::from(err.expect_err("expected error"));
+
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(err.offset(), Some(2));
+        assert_eq!(err.context_labels(), vec!["Header", "tag"]);
+        assert_eq!(err.expected(), Some("43981"));
+        assert_eq!(err.found(), Some("772"));
+        assert_eq!(err.to_string(),
+                   "unexpected value: expected 43981, found 772 at offset 0x2 in Header.tag");
+    }
+
+    #[test]
+    fn io_error_test() {
+        let mut reader = big::Read::new(Counting::new(Buffer::new(vec![1])));
+
+        let err = reader.read_u32().at(&reader).context("length").expect_err("expected error");
+
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+
+        let err = ParseError::from(err);
+
+        assert_eq!(err.offset(), Some(1));
+        assert!(err.to_string().ends_with(" at offset 0x1 in length"));
+    }
+}
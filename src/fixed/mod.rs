@@ -0,0 +1,78 @@
+//! The fixed module provides signed fixed-point number types, as used by font and media
+//! formats, which keep the exact stored bits so that they can be written back losslessly.
+
+use std::fmt;
+
+macro_rules! fixed {
+    ($(#[$doc:meta])* $name:ident, $bits:ty, $frac:expr) => {
+        $(#[$doc])*
+        #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub struct $name($bits);
+
+        impl $name {
+            /// The number of fractional bits.
+            pub const FRACTIONAL_BITS: u32 = $frac;
+
+            /// Constructs the number from its raw bits.
+            pub fn from_bits(bits: $bits) -> $name {
+                $name(bits)
+            }
+            /// Returns the raw bits of the number.
+            pub fn to_bits(self) -> $bits {
+                self.0
+            }
+            /// Constructs the number from a floating point value, rounding to the nearest
+            /// representable value and saturating at the limits of the type.
+            pub fn from_f64(v: f64) -> $name {
+                $name((v * (1u64 << $frac) as f64).round() as $bits)
+            }
+            /// Returns the value of the number as a floating point value, which is always exact.
+            pub fn to_f64(self) -> f64 {
+                self.0 as f64 / (1u64 << $frac) as f64
+            }
+        }
+
+        impl From<$name> for f64 {
+            fn from(v: $name) -> f64 {
+                v.to_f64()
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                self.to_f64().fmt(f)
+            }
+        }
+    }
+}
+
+fixed!(
+    /// A signed 32-bit fixed-point number with 16 fractional bits, such as the OpenType `Fixed`
+    /// type and MP4 matrix values.
+    Q16_16, i32, 16);
+fixed!(
+    /// A signed 16-bit fixed-point number with 8 fractional bits, such as the MP4 volume.
+    Q8_8, i16, 8);
+fixed!(
+    /// A signed 16-bit fixed-point number with 14 fractional bits, such as the OpenType
+    /// `F2DOT14` type.
+    F2Dot14, i16, 14);
+
+#[cfg(test)]
+mod tests {
+    use super::F2Dot14;
+    use super::Q16_16;
+    use super::Q8_8;
+
+    #[test]
+    fn conversion_test() {
+        assert_eq!(Q16_16::from_f64(1.5).to_bits(), 0x0001_8000);
+        assert_eq!(Q16_16::from_bits(-0x0001_8000).to_f64(), -1.5);
+        assert_eq!(Q8_8::from_f64(0.99609375).to_bits(), 0x00FF);
+        assert_eq!(Q8_8::from_f64(1000.0).to_bits(), i16::MAX);
+        assert_eq!(F2Dot14::from_bits(0x7FFF).to_f64(), 1.99993896484375);
+        assert_eq!(F2Dot14::from_f64(-2.0).to_bits(), -0x8000);
+        assert_eq!(F2Dot14::from_f64(0.000030517578125).to_bits(), 0x0001);
+        assert_eq!(f64::from(Q16_16::from_bits(0x0001_0000)), 1.0);
+    }
+}
@@ -0,0 +1,295 @@
+//! The cobs module provides streaming Consistent Overhead Byte Stuffing, which encodes frames
+//! so that they contain no zero bytes, allowing a single zero byte to delimit them.
+
+use std::io;
+use std::ops;
+use pending::Pending;
+use super::next_byte;
+use super::truncated;
+
+/// The largest number of non-zero bytes in a single COBS block.
+const MAX_BLOCK: usize = 254;
+
+/// The Encoder struct wraps an io::Write, COBS encoding everything written to it.
+///
+/// Frames are only delimited when `end_frame` is called. Output the embedded io::Write does not
+/// accept is kept and written first by the next call, so calls can be retried after an error.
+pub struct Encoder<W: io::Write> {
+    writer: W,
+    block: [u8; MAX_BLOCK + 1],
+    len: usize,
+    pending: Pending,
+}
+
+impl<W: io::Write> Encoder<W> {
+    /// Constructs a new `Encoder<W>`, wrapping the given io::Write.
+    pub fn new(writer: W) -> Encoder<W> {
+        Encoder {
+            writer,
+            block: [0; MAX_BLOCK + 1],
+            len: 0,
+            pending: Pending::new(),
+        }
+    }
+    /// Writes any buffered data, followed by the zero byte that ends the current frame.
+    ///
+    /// If this returns an error, calling it again finishes ending the same frame, rather than
+    /// adding an empty one.
+    pub fn end_frame(&mut self) -> io::Result<()> {
+        if self.pending.last() != Some(0) {
+            self.pending.drain(&mut self.writer)?;
+            self.emit();
+            self.pending.push(0);
+        }
+        self.pending.drain(&mut self.writer)
+    }
+    fn emit(&mut self) {
+        self.block[0] = self.len as u8 + 1;
+        self.pending.extend_from_slice(&self.block[..self.len + 1]);
+        self.len = 0;
+    }
+}
+
+impl<W: io::Write> io::Write for Encoder<W> {
+    /// The write function encodes the given bytes into the current frame, writing each block to
+    /// the embedded io::Write once it is complete.
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.pending.drain(&mut self.writer)?;
+        for &b in buf {
+            if self.len == MAX_BLOCK {
+                self.emit();
+            }
+            if b == 0 {
+                self.emit();
+            } else {
+                self.len += 1;
+                self.block[self.len] = b;
+            }
+        }
+        self.pending.accept(&mut self.writer, buf.len())
+    }
+    /// The flush function writes any complete blocks that have not yet been written, and
+    /// forwards the call to the embedded io::Write, without ending the current frame.
+    fn flush(&mut self) -> io::Result<()> {
+        self.pending.drain(&mut self.writer)?;
+        self.writer.flush()
+    }
+}
+
+impl<W: io::Write> ops::Deref for Encoder<W> {
+    type Target = W;
+
+    /// The deref function allows access to the wrapped io::Write.
+    fn deref(&self) -> &W {
+        &self.writer
+    }
+}
+
+#[derive(Clone, Copy)]
+enum State {
+    Start,
+    Block { remaining: u8, zero: bool },
+    End,
+    Eof,
+}
+
+/// The Decoder struct wraps an io::Read, decoding COBS frames from it.
+///
+/// Reading returns the contents of the current frame, and reaches end of file at the zero byte
+/// that ends it; `next_frame` then moves on to the following frame. The wrapped io::Read is read
+/// a byte at a time, so that nothing past the end of a frame is consumed, and so should be
+/// buffered.
+pub struct Decoder<R: io::Read> {
+    reader: R,
+    state: State,
+}
+
+impl<R: io::Read> Decoder<R> {
+    /// Constructs a new `Decoder<R>`, wrapping the given io::Read.
+    pub fn new(reader: R) -> Decoder<R> {
+        Decoder {
+            reader,
+            state: State::Start,
+        }
+    }
+    /// Reads the remainder of the current frame and moves on to the next, returning None if the
+    /// stream ended cleanly before the frame began.
+    pub fn read_frame(&mut self) -> io::Result<Option<Vec<u8>>> {
+        let mut data = Vec::new();
+        io::Read::read_to_end(self, &mut data)?;
+        if let State::Eof = self.state {
+            return Ok(None);
+        }
+        self.state = State::Start;
+        Ok(Some(data))
+    }
+    /// Discards the remainder of the current frame and moves on to the next.
+    pub fn next_frame(&mut self) -> io::Result<()> {
+        io::copy(self, &mut io::sink())?;
+        if let State::End = self.state {
+            self.state = State::Start;
+        }
+        Ok(())
+    }
+}
+
+impl<R: io::Read> io::Read for Decoder<R> {
+    /// The read function decodes bytes of the current frame from the embedded io::Read,
+    /// returning zero bytes once the end of the frame has been reached.
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut n = 0;
+        while n < buf.len() {
+            match self.state {
+                State::End | State::Eof => break,
+                State::Start => {
+                    self.state = match next_byte(&mut self.reader)? {
+                        None => State::Eof,
+                        Some(0) => State::End,
+                        Some(c) => State::Block {
+                            remaining: c - 1,
+                            zero: c < 0xFF,
+                        },
+                    }
+                }
+                State::Block { remaining: 0, zero } => {
+                    match next_byte(&mut self.reader)? {
+                        None => return Err(truncated()),
+                        Some(0) => self.state = State::End,
+                        Some(c) => {
+                            if zero {
+                                buf[n] = 0;
+                                n += 1;
+                            }
+                            self.state = State::Block {
+                                remaining: c - 1,
+                                zero: c < 0xFF,
+                            };
+                        }
+                    }
+                }
+                State::Block { remaining, zero } => {
+                    match next_byte(&mut self.reader)? {
+                        None => return Err(truncated()),
+                        Some(0) => {
+                            self.state = State::End;
+                            return Err(io::Error::new(io::ErrorKind::InvalidData,
+                                                      "unexpected end of frame"));
+                        }
+                        Some(b) => {
+                            buf[n] = b;
+                            n += 1;
+                            self.state = State::Block {
+                                remaining: remaining - 1,
+                                zero,
+                            };
+                        }
+                    }
+                }
+            }
+        }
+        Ok(n)
+    }
+}
+
+impl<R: io::Read> ops::Deref for Decoder<R> {
+    type Target = R;
+
+    /// The deref function allows access to the wrapped io::Read.
+    fn deref(&self) -> &R {
+        &self.reader
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+    use std::io::Read;
+    use std::io::Write;
+    use super::super::super::buffer::Buffer;
+    use super::super::super::endian::big;
+    use super::super::super::endian::EndianRead;
+    use super::super::super::endian::EndianWrite;
+    use super::super::super::testing;
+    use super::Decoder;
+    use super::Encoder;
+
+    fn encode(data: &[u8]) -> Vec<u8> {
+        let mut encoder = Encoder::new(Buffer::new(Vec::new()));
+
+        encoder.write_all(data).expect("unexpected error");
+        encoder.end_frame().expect("unexpected error");
+        encoder.to_vec()
+    }
+
+    #[test]
+    fn encode_test() {
+        let seq: Vec<u8> = (1..=255).collect();
+        let tests: Vec<(Vec<u8>, Vec<u8>)> =
+            vec![(vec![], vec![1, 0]),
+                 (vec![0], vec![1, 1, 0]),
+                 (vec![0, 0], vec![1, 1, 1, 0]),
+                 (vec![0, 0x11, 0], vec![1, 2, 0x11, 1, 0]),
+                 (vec![0x11, 0x22, 0, 0x33], vec![3, 0x11, 0x22, 2, 0x33, 0]),
+                 (vec![0x11, 0, 0, 0], vec![2, 0x11, 1, 1, 1, 0]),
+                 (seq[..254].to_vec(), [&[0xFF], &seq[..254], &[0]].concat()),
+                 ([&[0], &seq[..254]].concat(), [&[1, 0xFF], &seq[..254], &[0]].concat()),
+                 (seq.clone(), [&[0xFF], &seq[..254], &[2, 0xFF, 0]].concat()),
+                 ([&seq[1..], &[0]].concat(), [&[0xFF], &seq[1..], &[1, 1, 0]].concat()),
+                 ([&seq[2..], &[0, 1]].concat(), [&[0xFE], &seq[2..], &[2, 1, 0]].concat())];
+
+        for (data, encoded) in tests {
+            assert_eq!(encode(&data), encoded);
+
+            let mut decoder = Decoder::new(Buffer::new(encoded));
+
+            assert_eq!(decoder.read_frame().expect("unexpected error"), Some(data));
+            assert_eq!(decoder.read_frame().expect("unexpected error"), None);
+        }
+    }
+
+    #[test]
+    fn endian_test() {
+        let mut encoder = Encoder::new(Buffer::new(Vec::new()));
+
+        big::Write::new(&mut encoder).write_u32(0x00010200).expect("unexpected error");
+        encoder.end_frame().expect("unexpected error");
+        big::Write::new(&mut encoder).write_u16(0x0300).expect("unexpected error");
+        encoder.end_frame().expect("unexpected error");
+
+        let mut decoder = Decoder::new(Buffer::new(encoder.to_vec()));
+
+        assert_eq!(big::Read::new(&mut decoder).read_u16().expect("unexpected error"), 1);
+        decoder.next_frame().expect("unexpected error");
+        assert_eq!(big::Read::new(&mut decoder).read_u16().expect("unexpected error"),
+                   0x0300);
+        assert_eq!(big::Read::new(&mut decoder).read_u8().expect_err("expected error").kind(),
+                   io::ErrorKind::UnexpectedEof);
+        decoder.next_frame().expect("unexpected error");
+        assert_eq!(decoder.read(&mut [0; 4]).expect("unexpected error"), 0);
+    }
+
+    #[test]
+    fn invalid_test() {
+        let mut decoder = Decoder::new(Buffer::new(vec![3, 1, 0, 2, 5, 0]));
+
+        assert_eq!(decoder.read_frame().expect_err("expected error").kind(),
+                   io::ErrorKind::InvalidData);
+        decoder.next_frame().expect("unexpected error");
+        assert_eq!(decoder.read_frame().expect("unexpected error"), Some(vec![5]));
+
+        let mut decoder = Decoder::new(Buffer::new(vec![3, 1]));
+
+        assert_eq!(decoder.read_frame().expect_err("expected error").kind(),
+                   io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn chunked_test() {
+        let data: Vec<u8> = [&[1, 0, 2, 0, 3][..], &[7; 300], &[0]].concat();
+        let mut encoder = Encoder::new(testing::blocking(&[2, 3, 8, 260, 300, 309]));
+
+        testing::write_retrying(&mut encoder, &data).expect("unexpected error");
+        testing::retry(|| encoder.end_frame()).expect("unexpected error");
+        assert_eq!(encoder.to_vec(), encode(&data));
+    }
+}
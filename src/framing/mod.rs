@@ -0,0 +1,295 @@
+use std::io;
+use std::io::Read;
+use std::ops;
+use buffer::Buffer;
+use endian::big;
+use endian::little;
+use endian::EndianRead;
+use endian::EndianWrite;
+
+pub mod cobs;
+pub mod slip;
+
+/// The default maximum size of a frame, in bytes.
+pub const DEFAULT_MAX_FRAME_SIZE: usize = 8 << 20;
+
+/// The maximum number of bytes in a varint length.
+const MAX_VARINT_LEN: usize = 10;
+
+/// The ByteOrder enum determines the byte order of a fixed size frame length.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ByteOrder {
+    /// The most significant byte is first.
+    Big,
+    /// The least significant byte is first.
+    Little,
+}
+
+/// The Length enum determines how the length of each frame is encoded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Length {
+    /// An unsigned 16-bit integer, in the given byte order.
+    U16(ByteOrder),
+    /// An unsigned 32-bit integer, in the given byte order.
+    U32(ByteOrder),
+    /// An unsigned LEB128 varint, as used by protobuf.
+    Varint,
+}
+
+/// The Framed struct wraps an io::Read and/or io::Write to read and write whole messages, each
+/// prefixed with its length.
+pub struct Framed<T> {
+    inner: T,
+    length: Length,
+    max_frame_size: usize,
+}
+
+impl<T> Framed<T> {
+    /// Constructs a new `Framed<T>`, wrapping the given reader or writer, encoding frame lengths
+    /// as given and with a maximum frame size of DEFAULT_MAX_FRAME_SIZE.
+    pub fn new(inner: T, length: Length) -> Framed<T> {
+        Framed::with_max_frame_size(inner, length, DEFAULT_MAX_FRAME_SIZE)
+    }
+    /// Constructs a new `Framed<T>`, wrapping the given reader or writer, encoding frame lengths
+    /// as given and with the given maximum frame size.
+    pub fn with_max_frame_size(inner: T, length: Length, max_frame_size: usize) -> Framed<T> {
+        Framed {
+            inner,
+            length,
+            max_frame_size,
+        }
+    }
+    /// Returns the maximum frame size, in bytes.
+    pub fn max_frame_size(&self) -> usize {
+        self.max_frame_size
+    }
+}
+
+impl<T: io::Read> Framed<T> {
+    /// Reads a whole frame, returning None if the stream ends cleanly before the next frame.
+    ///
+    /// A frame longer than the maximum frame size is an InvalidData error, and is detected
+    /// before any of it is read.
+    pub fn read_frame(&mut self) -> io::Result<Option<Vec<u8>>> {
+        let len = match self.read_length()? {
+            Some(len) => len,
+            None => return Ok(None),
+        };
+        if len > self.max_frame_size as u64 {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "frame too large"));
+        }
+        let mut data = Vec::new();
+        (&mut self.inner).take(len).read_to_end(&mut data)?;
+        if (data.len() as u64) < len {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "truncated frame"));
+        }
+        Ok(Some(data))
+    }
+    /// Reads a whole frame into a Buffer, returning None if the stream ends cleanly before the
+    /// next frame.
+    pub fn read_frame_buffer(&mut self) -> io::Result<Option<Buffer>> {
+        match self.read_frame() {
+            Ok(data) => Ok(data.map(Buffer::new)),
+            Err(e) => Err(e),
+        }
+    }
+    fn read_length(&mut self) -> io::Result<Option<u64>> {
+        let mut first = [0];
+        loop {
+            match self.inner.read(&mut first) {
+                Ok(0) => return Ok(None),
+                Ok(_) => break,
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        let mut reader = (&first[..]).chain(&mut self.inner);
+        let len = match self.length {
+            Length::U16(ByteOrder::Big) => big::Read::new(reader).read_u16()? as u64,
+            Length::U16(ByteOrder::Little) => little::Read::new(reader).read_u16()? as u64,
+            Length::U32(ByteOrder::Big) => big::Read::new(reader).read_u32()? as u64,
+            Length::U32(ByteOrder::Little) => little::Read::new(reader).read_u32()? as u64,
+            Length::Varint => read_varint(&mut reader)?,
+        };
+        Ok(Some(len))
+    }
+}
+
+impl<T: io::Write> Framed<T> {
+    /// Writes the given data as a single frame.
+    ///
+    /// Data longer than the maximum frame size, or than the length encoding allows, is an
+    /// InvalidInput error, and nothing is written.
+    pub fn write_frame(&mut self, data: &[u8]) -> io::Result<()> {
+        let len = data.len();
+        if len > self.max_frame_size {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "frame too large"));
+        }
+        match self.length {
+            Length::U16(_) if len > u16::MAX as usize => {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput, "frame too large"));
+            }
+            Length::U32(_) if len > u32::MAX as usize => {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput, "frame too large"));
+            }
+            _ => {}
+        }
+        match self.length {
+            Length::U16(ByteOrder::Big) => big::Write::new(&mut self.inner).write_u16(len as u16)?,
+            Length::U16(ByteOrder::Little) => {
+                little::Write::new(&mut self.inner).write_u16(len as u16)?
+            }
+            Length::U32(ByteOrder::Big) => big::Write::new(&mut self.inner).write_u32(len as u32)?,
+            Length::U32(ByteOrder::Little) => {
+                little::Write::new(&mut self.inner).write_u32(len as u32)?
+            }
+            Length::Varint => write_varint(&mut self.inner, len as u64)?,
+        }
+        self.inner.write_all(data)
+    }
+    /// Flushes the wrapped io::Write.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<T> ops::Deref for Framed<T> {
+    type Target = T;
+
+    /// The deref function allows access to the wrapped reader or writer.
+    fn deref(&self) -> &T {
+        &self.inner
+    }
+}
+
+fn read_varint<R: io::Read>(r: &mut R) -> io::Result<u64> {
+    let mut v = 0u64;
+    for i in 0..MAX_VARINT_LEN {
+        let mut b = [0];
+        r.read_exact(&mut b)?;
+        let bits = (b[0] & 0x7F) as u64;
+        if i == MAX_VARINT_LEN - 1 && bits > 1 {
+            break;
+        }
+        v |= bits << (7 * i);
+        if b[0] & 0x80 == 0 {
+            return Ok(v);
+        }
+    }
+    Err(io::Error::new(io::ErrorKind::InvalidData, "varint too long"))
+}
+
+fn write_varint<W: io::Write>(w: &mut W, mut v: u64) -> io::Result<()> {
+    let mut buf = [0; MAX_VARINT_LEN];
+    let mut n = 0;
+    loop {
+        buf[n] = (v & 0x7F) as u8;
+        v >>= 7;
+        if v == 0 {
+            break;
+        }
+        buf[n] |= 0x80;
+        n += 1;
+    }
+    w.write_all(&buf[..n + 1])
+}
+
+/// Reads a single byte for the COBS and SLIP decoders, returning None at end of file.
+fn next_byte<R: io::Read>(r: &mut R) -> io::Result<Option<u8>> {
+    let mut b = [0];
+    loop {
+        match r.read(&mut b) {
+            Ok(0) => return Ok(None),
+            Ok(_) => return Ok(Some(b[0])),
+            Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+fn truncated() -> io::Error {
+    io::Error::new(io::ErrorKind::UnexpectedEof, "truncated frame")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+    use super::super::buffer::Buffer;
+    use super::ByteOrder;
+    use super::Framed;
+    use super::Length;
+
+    #[test]
+    fn fixed_test() {
+        let mut writer = Framed::new(Buffer::new(Vec::new()), Length::U16(ByteOrder::Big));
+
+        writer.write_frame(b"abc").expect("unexpected error");
+        writer.write_frame(b"").expect("unexpected error");
+        assert_eq!(**writer, vec![0, 3, b'a', b'b', b'c', 0, 0]);
+
+        let mut writer = Framed::new(Buffer::new(Vec::new()), Length::U32(ByteOrder::Little));
+
+        writer.write_frame(b"de").expect("unexpected error");
+        assert_eq!(**writer, vec![2, 0, 0, 0, b'd', b'e']);
+
+        let mut reader = Framed::new(Buffer::new(vec![2, 0, 0, 0, b'd', b'e', 1, 0, 0, 0, b'f']),
+                                     Length::U32(ByteOrder::Little));
+
+        assert_eq!(reader.read_frame().expect("unexpected error"), Some(b"de".to_vec()));
+        assert_eq!(**reader.read_frame_buffer().expect("unexpected error").expect("no frame"),
+                   b"f".to_vec());
+        assert_eq!(reader.read_frame().expect("unexpected error"), None);
+    }
+
+    #[test]
+    fn varint_test() {
+        let data = vec![7; 300];
+        let mut writer = Framed::new(Buffer::new(Vec::new()), Length::Varint);
+
+        writer.write_frame(&data).expect("unexpected error");
+        assert_eq!(&writer[..2], &[0xAC, 0x02]);
+
+        let mut reader = Framed::new(Buffer::new(writer.to_vec()), Length::Varint);
+
+        assert_eq!(reader.read_frame().expect("unexpected error"), Some(data));
+        assert_eq!(reader.read_frame().expect("unexpected error"), None);
+
+        let mut reader = Framed::new(Buffer::new(vec![0xFF; 11]), Length::Varint);
+
+        assert_eq!(reader.read_frame().expect_err("expected error").kind(),
+                   io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn limit_test() {
+        let mut writer = Framed::with_max_frame_size(Buffer::new(Vec::new()),
+                                                     Length::U32(ByteOrder::Big),
+                                                     4);
+
+        assert_eq!(writer.write_frame(b"abcde").expect_err("expected error").kind(),
+                   io::ErrorKind::InvalidInput);
+        assert!(writer.is_empty());
+
+        let mut writer = Framed::new(Buffer::new(Vec::new()), Length::U16(ByteOrder::Big));
+
+        assert!(writer.write_frame(&[0; 0x10000]).is_err());
+
+        let mut reader = Framed::with_max_frame_size(Buffer::new(vec![0, 0, 0, 5, 1, 2, 3, 4, 5]),
+                                                     Length::U32(ByteOrder::Big),
+                                                     4);
+
+        assert_eq!(reader.read_frame().expect_err("expected error").kind(),
+                   io::ErrorKind::InvalidData);
+
+        let mut reader = Framed::new(Buffer::new(vec![0, 0, 0, 5, 1, 2]),
+                                     Length::U32(ByteOrder::Big));
+
+        assert_eq!(reader.read_frame().expect_err("expected error").kind(),
+                   io::ErrorKind::UnexpectedEof);
+
+        let mut reader = Framed::new(Buffer::new(vec![0]), Length::U16(ByteOrder::Big));
+
+        assert_eq!(reader.read_frame().expect_err("expected error").kind(),
+                   io::ErrorKind::UnexpectedEof);
+    }
+}
@@ -0,0 +1,254 @@
+//! The slip module provides streaming SLIP (RFC 1055) framing, which delimits frames with an
+//! END byte, escaping any END or ESC bytes within them.
+
+use std::io;
+use std::ops;
+use pending::Pending;
+use super::next_byte;
+use super::truncated;
+
+/// The byte that ends a frame.
+pub const END: u8 = 0xC0;
+/// The byte that starts an escape sequence.
+pub const ESC: u8 = 0xDB;
+/// The escaped form of END, following ESC.
+pub const ESC_END: u8 = 0xDC;
+/// The escaped form of ESC, following ESC.
+pub const ESC_ESC: u8 = 0xDD;
+
+/// The Encoder struct wraps an io::Write, SLIP encoding everything written to it.
+///
+/// Frames are only delimited when `end_frame` is called.
+pub struct Encoder<W: io::Write> {
+    writer: W,
+    pending: Pending,
+}
+
+impl<W: io::Write> Encoder<W> {
+    /// Constructs a new `Encoder<W>`, wrapping the given io::Write.
+    pub fn new(writer: W) -> Encoder<W> {
+        Encoder {
+            writer,
+            pending: Pending::new(),
+        }
+    }
+    /// Writes the END byte that ends the current frame.
+    ///
+    /// After an error, calling this again writes the same END byte, rather than a second one.
+    pub fn end_frame(&mut self) -> io::Result<()> {
+        if self.pending.last() != Some(END) {
+            self.pending.drain(&mut self.writer)?;
+            self.pending.push(END);
+        }
+        self.pending.drain(&mut self.writer)
+    }
+}
+
+impl<W: io::Write> io::Write for Encoder<W> {
+    /// The write function escapes the given bytes and writes them to the embedded io::Write.
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.pending.drain(&mut self.writer)?;
+        for &b in buf {
+            match b {
+                END => self.pending.extend_from_slice(&[ESC, ESC_END]),
+                ESC => self.pending.extend_from_slice(&[ESC, ESC_ESC]),
+                _ => self.pending.push(b),
+            }
+        }
+        self.pending.accept(&mut self.writer, buf.len())
+    }
+    /// The flush function writes any escaped bytes that have not yet been written, and forwards
+    /// the call to the embedded io::Write, without ending the current frame.
+    fn flush(&mut self) -> io::Result<()> {
+        self.pending.drain(&mut self.writer)?;
+        self.writer.flush()
+    }
+}
+
+impl<W: io::Write> ops::Deref for Encoder<W> {
+    type Target = W;
+
+    /// The deref function allows access to the wrapped io::Write.
+    fn deref(&self) -> &W {
+        &self.writer
+    }
+}
+
+#[derive(Clone, Copy)]
+enum State {
+    Frame,
+    End,
+    Eof,
+}
+
+/// The Decoder struct wraps an io::Read, decoding SLIP frames from it.
+///
+/// Reading returns the contents of the current frame, and reaches end of file at the END byte
+/// that ends it; `next_frame` then moves on to the following frame. The wrapped io::Read is read
+/// a byte at a time, so that nothing past the end of a frame is consumed, and so should be
+/// buffered.
+pub struct Decoder<R: io::Read> {
+    reader: R,
+    state: State,
+    started: bool,
+}
+
+impl<R: io::Read> Decoder<R> {
+    /// Constructs a new `Decoder<R>`, wrapping the given io::Read.
+    pub fn new(reader: R) -> Decoder<R> {
+        Decoder {
+            reader,
+            state: State::Frame,
+            started: false,
+        }
+    }
+    /// Reads the remainder of the current frame and moves on to the next, returning None if the
+    /// stream ended cleanly before the frame began.
+    ///
+    /// As recommended by RFC 1055, empty frames, such as those caused by a sender starting each
+    /// frame with an END byte, are skipped.
+    pub fn read_frame(&mut self) -> io::Result<Option<Vec<u8>>> {
+        loop {
+            let mut data = Vec::new();
+            io::Read::read_to_end(self, &mut data)?;
+            if let State::Eof = self.state {
+                return Ok(None);
+            }
+            self.reset();
+            if !data.is_empty() {
+                return Ok(Some(data));
+            }
+        }
+    }
+    /// Discards the remainder of the current frame and moves on to the next.
+    pub fn next_frame(&mut self) -> io::Result<()> {
+        io::copy(self, &mut io::sink())?;
+        if let State::End = self.state {
+            self.reset();
+        }
+        Ok(())
+    }
+    fn reset(&mut self) {
+        self.state = State::Frame;
+        self.started = false;
+    }
+}
+
+impl<R: io::Read> io::Read for Decoder<R> {
+    /// The read function decodes bytes of the current frame from the embedded io::Read,
+    /// returning zero bytes once the end of the frame has been reached.
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut n = 0;
+        while n < buf.len() {
+            if let State::End | State::Eof = self.state {
+                break;
+            }
+            let b = match next_byte(&mut self.reader)? {
+                Some(b) => b,
+                None if self.started => return Err(truncated()),
+                None => {
+                    self.state = State::Eof;
+                    break;
+                }
+            };
+            self.started = true;
+            buf[n] = match b {
+                END => {
+                    self.state = State::End;
+                    break;
+                }
+                ESC => {
+                    match next_byte(&mut self.reader)? {
+                        Some(ESC_END) => END,
+                        Some(ESC_ESC) => ESC,
+                        Some(_) => {
+                            return Err(io::Error::new(io::ErrorKind::InvalidData,
+                                                      "invalid escape sequence"))
+                        }
+                        None => return Err(truncated()),
+                    }
+                }
+                _ => b,
+            };
+            n += 1;
+        }
+        Ok(n)
+    }
+}
+
+impl<R: io::Read> ops::Deref for Decoder<R> {
+    type Target = R;
+
+    /// The deref function allows access to the wrapped io::Read.
+    fn deref(&self) -> &R {
+        &self.reader
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+    use std::io::Write;
+    use super::super::super::buffer::Buffer;
+    use super::super::super::endian::little;
+    use super::super::super::endian::EndianRead;
+    use super::super::super::endian::EndianWrite;
+    use super::super::super::sticky::write::Sticky;
+    use super::super::super::testing;
+    use super::Decoder;
+    use super::Encoder;
+
+    #[test]
+    fn encode_test() {
+        let mut encoder = Encoder::new(Sticky::new(Buffer::new(Vec::new())));
+
+        encoder.write_all(&[1, 0xC0, 2, 0xDB, 3]).expect("unexpected error");
+        encoder.end_frame().expect("unexpected error");
+        little::Write::new(&mut encoder).write_u16(0xC0DB).expect("unexpected error");
+        encoder.end_frame().expect("unexpected error");
+        assert_eq!(***encoder,
+                   vec![1, 0xDB, 0xDC, 2, 0xDB, 0xDD, 3, 0xC0, 0xDB, 0xDD, 0xDB, 0xDC, 0xC0]);
+    }
+
+    #[test]
+    fn decode_test() {
+        let mut decoder = Decoder::new(Buffer::new(vec![0xC0, 1, 0xDB, 0xDC, 2, 0xDB, 0xDD, 3,
+                                                        0xC0, 0xDB, 0xDD, 0xDB, 0xDC,
+                                                        0xC0, 4, 5, 0xC0]));
+
+        assert_eq!(decoder.read_frame().expect("unexpected error"),
+                   Some(vec![1, 0xC0, 2, 0xDB, 3]));
+        assert_eq!(little::Read::new(&mut decoder).read_u16().expect("unexpected error"),
+                   0xC0DB);
+        decoder.next_frame().expect("unexpected error");
+        assert_eq!(little::Read::new(&mut decoder).read_u8().expect("unexpected error"), 4);
+        decoder.next_frame().expect("unexpected error");
+        assert_eq!(decoder.read_frame().expect("unexpected error"), None);
+    }
+
+    #[test]
+    fn invalid_test() {
+        let mut decoder = Decoder::new(Buffer::new(vec![1, 0xDB, 2, 0xC0, 3, 0xC0]));
+
+        assert_eq!(decoder.read_frame().expect_err("expected error").kind(),
+                   io::ErrorKind::InvalidData);
+        decoder.next_frame().expect("unexpected error");
+        assert_eq!(decoder.read_frame().expect("unexpected error"), Some(vec![3]));
+
+        let mut decoder = Decoder::new(Buffer::new(vec![1, 2]));
+
+        assert_eq!(decoder.read_frame().expect_err("expected error").kind(),
+                   io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn chunked_test() {
+        let mut encoder = Encoder::new(testing::blocking(&[1, 2, 5, 9, 10]));
+
+        testing::write_retrying(&mut encoder, &[1, 0xC0, 2, 0xDB, 3, 0xC0])
+            .expect("unexpected error");
+        testing::retry(|| encoder.end_frame()).expect("unexpected error");
+        assert_eq!(encoder.to_vec(),
+                   vec![1, 0xDB, 0xDC, 2, 0xDB, 0xDD, 3, 0xDB, 0xDC, 0xC0]);
+    }
+}
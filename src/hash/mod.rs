@@ -0,0 +1,271 @@
+use std::io;
+use std::ops;
+
+/// The Digest trait is implemented by checksums and hashes that can be updated incrementally,
+/// allowing them to be used with the Hashing wrapper.
+pub trait Digest {
+    /// The type of the computed checksum.
+    type Output;
+
+    /// Updates the digest with the given bytes.
+    fn update(&mut self, data: &[u8]);
+    /// Returns the checksum of all bytes seen so far.
+    fn finish(&self) -> Self::Output;
+    /// Resets the digest to its initial state.
+    fn reset(&mut self);
+}
+
+const fn crc_table(poly: u32) -> [u32; 256] {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut c = i as u32;
+        let mut k = 0;
+        while k < 8 {
+            c = if c & 1 == 1 { poly ^ (c >> 1) } else { c >> 1 };
+            k += 1;
+        }
+        table[i] = c;
+        i += 1;
+    }
+    table
+}
+
+static CRC32_TABLE: [u32; 256] = crc_table(0xEDB8_8320);
+static CRC32C_TABLE: [u32; 256] = crc_table(0x82F6_3B78);
+
+fn crc_update(table: &[u32; 256], crc: u32, data: &[u8]) -> u32 {
+    let mut c = !crc;
+    for b in data {
+        c = table[((c ^ *b as u32) & 0xFF) as usize] ^ (c >> 8);
+    }
+    !c
+}
+
+/// The Crc32 struct computes the CRC-32 (IEEE 802.3) checksum, as used by PNG, zip and gzip.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Crc32 {
+    crc: u32,
+}
+
+impl Crc32 {
+    /// Constructs a new `Crc32`.
+    pub fn new() -> Crc32 {
+        Crc32::default()
+    }
+}
+
+impl Digest for Crc32 {
+    type Output = u32;
+
+    fn update(&mut self, data: &[u8]) {
+        self.crc = crc_update(&CRC32_TABLE, self.crc, data);
+    }
+    fn finish(&self) -> u32 {
+        self.crc
+    }
+    fn reset(&mut self) {
+        self.crc = 0;
+    }
+}
+
+/// The Crc32c struct computes the CRC-32C (Castagnoli) checksum, as used by iSCSI and ext4.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Crc32c {
+    crc: u32,
+}
+
+impl Crc32c {
+    /// Constructs a new `Crc32c`.
+    pub fn new() -> Crc32c {
+        Crc32c::default()
+    }
+}
+
+impl Digest for Crc32c {
+    type Output = u32;
+
+    fn update(&mut self, data: &[u8]) {
+        self.crc = crc_update(&CRC32C_TABLE, self.crc, data);
+    }
+    fn finish(&self) -> u32 {
+        self.crc
+    }
+    fn reset(&mut self) {
+        self.crc = 0;
+    }
+}
+
+/// The largest number of bytes that can be summed before the Adler-32 sums must be reduced to
+/// avoid overflow.
+const ADLER_NMAX: usize = 5552;
+const ADLER_MOD: u32 = 65521;
+
+/// The Adler32 struct computes the Adler-32 checksum, as used by zlib.
+#[derive(Clone, Copy, Debug)]
+pub struct Adler32 {
+    a: u32,
+    b: u32,
+}
+
+impl Adler32 {
+    /// Constructs a new `Adler32`.
+    pub fn new() -> Adler32 {
+        Adler32 { a: 1, b: 0 }
+    }
+}
+
+impl Default for Adler32 {
+    fn default() -> Adler32 {
+        Adler32::new()
+    }
+}
+
+impl Digest for Adler32 {
+    type Output = u32;
+
+    fn update(&mut self, data: &[u8]) {
+        for chunk in data.chunks(ADLER_NMAX) {
+            for b in chunk {
+                self.a += *b as u32;
+                self.b += self.a;
+            }
+            self.a %= ADLER_MOD;
+            self.b %= ADLER_MOD;
+        }
+    }
+    fn finish(&self) -> u32 {
+        self.b << 16 | self.a
+    }
+    fn reset(&mut self) {
+        *self = Adler32::new();
+    }
+}
+
+/// The Hashing struct wraps an io::Read and/or io::Write, updating a Digest with every byte
+/// that is read or written through it.
+///
+/// To checksum a single section of a stream, such as a PNG chunk, wrap a mutable reference to
+/// the stream and drop the Hashing once the checksum has been taken.
+pub struct Hashing<T, D: Digest> {
+    inner: T,
+    digest: D,
+}
+
+impl<T, D: Digest> Hashing<T, D> {
+    /// Constructs a new `Hashing<T, D>`, wrapping the given reader or writer and updating the
+    /// given Digest.
+    pub fn new(inner: T, digest: D) -> Hashing<T, D> {
+        Hashing { inner, digest }
+    }
+    /// Returns the Digest.
+    pub fn digest(&self) -> &D {
+        &self.digest
+    }
+    /// Returns the checksum of all bytes read or written so far.
+    pub fn finish(&self) -> D::Output {
+        self.digest.finish()
+    }
+    /// Returns the checksum of all bytes read or written so far, and resets the Digest.
+    pub fn reset(&mut self) -> D::Output {
+        let sum = self.digest.finish();
+        self.digest.reset();
+        sum
+    }
+}
+
+impl<T: io::Read, D: Digest> io::Read for Hashing<T, D> {
+    /// The read function forwards the call to the embedded io::Read, updating the Digest with
+    /// the bytes read.
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.digest.update(&buf[..n]);
+        Ok(n)
+    }
+}
+
+impl<T: io::Write, D: Digest> io::Write for Hashing<T, D> {
+    /// The write function forwards the call to the embedded io::Write, updating the Digest with
+    /// the bytes written.
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.digest.update(&buf[..n]);
+        Ok(n)
+    }
+    /// The flush function forwards the call to the embedded io::Write.
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<T, D: Digest> ops::Deref for Hashing<T, D> {
+    type Target = T;
+
+    /// The deref function allows access to the wrapped reader or writer.
+    fn deref(&self) -> &T {
+        &self.inner
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+    use std::io::Write;
+    use super::super::buffer::Buffer;
+    use super::super::endian::big;
+    use super::super::endian::EndianRead;
+    use super::super::endian::EndianWrite;
+    use super::super::sticky::write::Sticky;
+    use super::Adler32;
+    use super::Crc32;
+    use super::Crc32c;
+    use super::Digest;
+    use super::Hashing;
+
+    #[test]
+    fn digest_test() {
+        let mut crc32 = Crc32::new();
+        let mut crc32c = Crc32c::new();
+        let mut adler32 = Adler32::new();
+
+        crc32.update(b"1234");
+        crc32.update(b"56789");
+        crc32c.update(b"123456789");
+        adler32.update(b"Wikipedia");
+        assert_eq!(crc32.finish(), 0xCBF43926);
+        assert_eq!(crc32c.finish(), 0xE3069283);
+        assert_eq!(adler32.finish(), 0x11E60398);
+
+        let mut adler32 = Adler32::new();
+
+        adler32.update(&[0xFF; 100000]);
+        assert_eq!(adler32.finish(), 0x149A302C);
+    }
+
+    #[test]
+    fn write_test() {
+        let mut out = big::Write::new(Buffer::new(Vec::new()));
+
+        {
+            let mut chunk = big::Write::new(Hashing::new(Sticky::new(&mut out), Crc32::new()));
+
+            chunk.write_all(b"IEND").expect("unexpected error");
+            assert_eq!(chunk.finish(), 0xAE426082);
+            assert_eq!(chunk.finish(), chunk.digest().finish());
+        }
+
+        out.write_u32(0xAE426082).expect("unexpected error");
+        assert_eq!(**out, vec![b'I', b'E', b'N', b'D', 0xAE, 0x42, 0x60, 0x82]);
+    }
+
+    #[test]
+    fn read_test() {
+        let mut reader = big::Read::new(Hashing::new(Buffer::new(b"123456789\x00".to_vec()),
+                                                     Crc32c::new()));
+        let mut buf = [0; 9];
+
+        reader.read_exact(&mut buf).expect("unexpected error");
+        assert_eq!(reader.finish(), 0xE3069283);
+        assert_eq!(reader.read_u8().expect("unexpected error"), 0);
+    }
+}
@@ -0,0 +1,501 @@
+//! The hex module provides hex dumps in the layouts of `xxd` and `hexdump -C`, and adapters for
+//! encoding bytes as hex and decoding them again.
+
+use std::fmt;
+use std::io;
+use std::ops;
+use pending::Pending;
+
+/// The number of bytes shown on each line of a hex dump.
+const LINE_LEN: usize = 16;
+
+/// The Style enum determines the layout of a hex dump.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Style {
+    /// The layout of `xxd`, with bytes in groups of two.
+    Xxd,
+    /// The canonical hex+ASCII layout of `hexdump -C`, ending with a line containing the total
+    /// length.
+    Canonical,
+}
+
+fn write_line<W: fmt::Write>(w: &mut W, offset: u64, line: &[u8], style: Style) -> fmt::Result {
+    match style {
+        Style::Xxd => {
+            write!(w, "{:08x}: ", offset)?;
+            for i in 0..LINE_LEN {
+                match line.get(i) {
+                    Some(b) => write!(w, "{:02x}", b)?,
+                    None => w.write_str("  ")?,
+                }
+                if i % 2 == 1 {
+                    w.write_char(' ')?;
+                }
+            }
+            w.write_char(' ')?;
+        }
+        Style::Canonical => {
+            write!(w, "{:08x}  ", offset)?;
+            for i in 0..LINE_LEN {
+                match line.get(i) {
+                    Some(b) => write!(w, "{:02x} ", b)?,
+                    None => w.write_str("   ")?,
+                }
+                if i == LINE_LEN / 2 - 1 {
+                    w.write_char(' ')?;
+                }
+            }
+            w.write_str(" |")?;
+        }
+    }
+    for &b in line {
+        w.write_char(if b == b' ' || b.is_ascii_graphic() {
+                         b as char
+                     } else {
+                         '.'
+                     })?;
+    }
+    if style == Style::Canonical {
+        w.write_char('|')?;
+    }
+    w.write_char('\n')
+}
+
+fn write_end<W: fmt::Write>(w: &mut W, offset: u64, style: Style) -> fmt::Result {
+    match style {
+        Style::Canonical if offset > 0 => writeln!(w, "{:08x}", offset),
+        _ => Ok(()),
+    }
+}
+
+/// The HexDump struct displays a byte slice, such as the contents of a Buffer, as a hex dump.
+pub struct HexDump<'a> {
+    data: &'a [u8],
+    style: Style,
+    offset: u64,
+}
+
+impl<'a> HexDump<'a> {
+    /// Constructs a new `HexDump`, displaying the given bytes in the given style.
+    pub fn new(data: &'a [u8], style: Style) -> HexDump<'a> {
+        HexDump::with_offset(data, style, 0)
+    }
+    /// Constructs a new `HexDump`, displaying the given bytes in the given style, with offsets
+    /// starting from the given offset.
+    pub fn with_offset(data: &'a [u8], style: Style, offset: u64) -> HexDump<'a> {
+        HexDump {
+            data,
+            style,
+            offset,
+        }
+    }
+}
+
+impl<'a> fmt::Display for HexDump<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut offset = self.offset;
+        for line in self.data.chunks(LINE_LEN) {
+            write_line(f, offset, line, self.style)?;
+            offset += line.len() as u64;
+        }
+        write_end(f, offset, self.style)
+    }
+}
+
+/// The Dump struct wraps an io::Read and/or io::Write, writing a hex dump of every byte that is
+/// read or written through it to a second io::Write, such as io::stderr.
+///
+/// Each line is written once it is complete; `finish` writes any partial final line.
+///
+/// Errors from the wrapped reader or writer are returned as normal. As with the Tee wrapper, the
+/// first error from the dump output is stored, after which nothing more is dumped, and reading
+/// or writing continues unaffected.
+pub struct Dump<T, W: io::Write> {
+    inner: T,
+    output: W,
+    style: Style,
+    offset: u64,
+    line: Vec<u8>,
+    error: Option<io::Error>,
+    finished: bool,
+}
+
+impl<T, W: io::Write> Dump<T, W> {
+    /// Constructs a new `Dump<T, W>`, wrapping the given reader or writer and writing a hex dump
+    /// in the given style to the given io::Write.
+    pub fn new(inner: T, output: W, style: Style) -> Dump<T, W> {
+        Dump {
+            inner,
+            output,
+            style,
+            offset: 0,
+            line: Vec::with_capacity(LINE_LEN),
+            error: None,
+            finished: false,
+        }
+    }
+    /// Returns the error from the dump output, if there has been one.
+    pub fn error(self) -> Option<io::Error> {
+        self.error
+    }
+    /// Returns true if there has been an error from the dump output.
+    pub fn has_error(&self) -> bool {
+        self.error.is_some()
+    }
+    /// Writes any partial final line of the hex dump, returning the error from the dump output
+    /// if there has been one.
+    ///
+    /// Only the first call writes anything; later calls just return the stored error, if any.
+    pub fn finish(&mut self) -> io::Result<()> {
+        if !self.finished && self.error.is_none() {
+            let mut s = String::new();
+            if !self.line.is_empty() {
+                let _ = write_line(&mut s, self.offset, &self.line, self.style);
+                self.offset += self.line.len() as u64;
+                self.line.clear();
+            }
+            let _ = write_end(&mut s, self.offset, self.style);
+            if let Err(e) = self.output.write_all(s.as_bytes()) {
+                self.error = Some(e);
+            }
+        }
+        self.finished = true;
+        match self.error {
+            Some(ref e) => Err(io::Error::new(e.kind(), e.to_string())),
+            None => Ok(()),
+        }
+    }
+    fn dump(&mut self, data: &[u8]) {
+        if self.error.is_some() {
+            return;
+        }
+        let mut s = String::new();
+        for &b in data {
+            self.line.push(b);
+            if self.line.len() == LINE_LEN {
+                let _ = write_line(&mut s, self.offset, &self.line, self.style);
+                self.offset += LINE_LEN as u64;
+                self.line.clear();
+            }
+        }
+        if let Err(e) = self.output.write_all(s.as_bytes()) {
+            self.error = Some(e);
+        }
+    }
+}
+
+impl<T: io::Read, W: io::Write> io::Read for Dump<T, W> {
+    /// The read function forwards the call to the embedded io::Read, dumping the bytes read.
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.dump(&buf[..n]);
+        Ok(n)
+    }
+}
+
+impl<T: io::Write, W: io::Write> io::Write for Dump<T, W> {
+    /// The write function forwards the call to the embedded io::Write, dumping the bytes
+    /// written.
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.dump(&buf[..n]);
+        Ok(n)
+    }
+    /// The flush function forwards the call to the embedded io::Write and the dump output,
+    /// storing any error from the dump output.
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()?;
+        if self.error.is_none() {
+            if let Err(e) = self.output.flush() {
+                self.error = Some(e);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<T, W: io::Write> ops::Deref for Dump<T, W> {
+    type Target = T;
+
+    /// The deref function allows access to the wrapped reader or writer.
+    fn deref(&self) -> &T {
+        &self.inner
+    }
+}
+
+const HEX_DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+/// Returns the given bytes encoded as lowercase hex.
+pub fn encode(data: &[u8]) -> String {
+    let mut s = String::with_capacity(data.len() * 2);
+    for &b in data {
+        s.push(HEX_DIGITS[(b >> 4) as usize] as char);
+        s.push(HEX_DIGITS[(b & 0xF) as usize] as char);
+    }
+    s
+}
+
+/// Returns the bytes encoded in the given hex string, ignoring any whitespace.
+pub fn decode(s: &str) -> io::Result<Vec<u8>> {
+    let mut data = Vec::with_capacity(s.len() / 2);
+    match io::Read::read_to_end(&mut Decoder::new(s.as_bytes()), &mut data) {
+        Ok(_) => Ok(data),
+        Err(e) => Err(e),
+    }
+}
+
+/// The Encoder struct wraps an io::Write, writing everything written to it as lowercase hex.
+pub struct Encoder<W: io::Write> {
+    writer: W,
+    pending: Pending,
+}
+
+impl<W: io::Write> Encoder<W> {
+    /// Constructs a new `Encoder<W>`, wrapping the given io::Write.
+    pub fn new(writer: W) -> Encoder<W> {
+        Encoder {
+            writer,
+            pending: Pending::new(),
+        }
+    }
+}
+
+impl<W: io::Write> io::Write for Encoder<W> {
+    /// The write function writes the given bytes as hex to the embedded io::Write.
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.pending.drain(&mut self.writer)?;
+        self.pending.extend_from_slice(encode(buf).as_bytes());
+        self.pending.accept(&mut self.writer, buf.len())
+    }
+    /// The flush function writes any hex that has not yet been written, and forwards the call
+    /// to the embedded io::Write.
+    fn flush(&mut self) -> io::Result<()> {
+        self.pending.drain(&mut self.writer)?;
+        self.writer.flush()
+    }
+}
+
+impl<W: io::Write> ops::Deref for Encoder<W> {
+    type Target = W;
+
+    /// The deref function allows access to the wrapped io::Write.
+    fn deref(&self) -> &W {
+        &self.writer
+    }
+}
+
+/// The Decoder struct wraps an io::Read of hex, reading the bytes it encodes. Upper and lower
+/// case digits are accepted, and whitespace is ignored.
+pub struct Decoder<R: io::Read> {
+    reader: R,
+    high: Option<u8>,
+    error: Option<io::Error>,
+}
+
+impl<R: io::Read> Decoder<R> {
+    /// Constructs a new `Decoder<R>`, wrapping the given io::Read.
+    pub fn new(reader: R) -> Decoder<R> {
+        Decoder {
+            reader,
+            high: None,
+            error: None,
+        }
+    }
+}
+
+impl<R: io::Read> io::Read for Decoder<R> {
+    /// The read function reads hex from the embedded io::Read, decoding it into the given slice.
+    ///
+    /// If an invalid digit follows bytes that have already been decoded, those bytes are
+    /// returned, and the error is returned by the next call.
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if let Some(e) = self.error.take() {
+            return Err(e);
+        }
+        let mut scratch = [0; 512];
+        let limit = if buf.len() < scratch.len() / 2 {
+            buf.len() * 2
+        } else {
+            scratch.len()
+        };
+        let mut n = 0;
+        while n == 0 && limit > 0 {
+            let m = self.reader.read(&mut scratch[..limit])?;
+            if m == 0 {
+                if self.high.is_some() {
+                    return Err(io::Error::new(io::ErrorKind::UnexpectedEof,
+                                              "odd number of hex digits"));
+                }
+                break;
+            }
+            for &c in &scratch[..m] {
+                let v = match c {
+                    b'0'..=b'9' => c - b'0',
+                    b'a'..=b'f' => c - b'a' + 10,
+                    b'A'..=b'F' => c - b'A' + 10,
+                    _ if c.is_ascii_whitespace() => continue,
+                    _ => {
+                        let e = io::Error::new(io::ErrorKind::InvalidData, "invalid hex digit");
+                        if n == 0 {
+                            return Err(e);
+                        }
+                        self.error = Some(e);
+                        return Ok(n);
+                    }
+                };
+                match self.high.take() {
+                    Some(high) => {
+                        buf[n] = high << 4 | v;
+                        n += 1;
+                    }
+                    None => self.high = Some(v),
+                }
+            }
+        }
+        Ok(n)
+    }
+}
+
+impl<R: io::Read> ops::Deref for Decoder<R> {
+    type Target = R;
+
+    /// The deref function allows access to the wrapped io::Read.
+    fn deref(&self) -> &R {
+        &self.reader
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+    use std::io::Read;
+    use std::io::Write;
+    use super::super::buffer::Buffer;
+    use super::super::endian::big;
+    use super::super::endian::EndianRead;
+    use super::super::endian::EndianWrite;
+    use super::super::testing;
+    use super::super::testing::Chunked;
+    use super::super::testing::Chunking;
+    use super::Dump;
+    use super::HexDump;
+    use super::Style;
+
+    const DATA: &[u8] = b"Hello, World!\n\x00\x01\xffabcdefghijklmnopq";
+
+    #[test]
+    fn hexdump_test() {
+        let buffer = Buffer::new(DATA.to_vec());
+
+        assert_eq!(HexDump::new(&buffer, Style::Xxd).to_string(),
+                   "00000000: 4865 6c6c 6f2c 2057 6f72 6c64 210a 0001  Hello, World!...\n\
+                    00000010: ff61 6263 6465 6667 6869 6a6b 6c6d 6e6f  .abcdefghijklmno\n\
+                    00000020: 7071                                     pq\n");
+        assert_eq!(HexDump::new(&buffer, Style::Canonical).to_string(),
+                   "00000000  48 65 6c 6c 6f 2c 20 57  6f 72 6c 64 21 0a 00 01  \
+                    |Hello, World!...|\n\
+                    00000010  ff 61 62 63 64 65 66 67  68 69 6a 6b 6c 6d 6e 6f  \
+                    |.abcdefghijklmno|\n\
+                    00000020  70 71                                             |pq|\n\
+                    00000022\n");
+        assert_eq!(HexDump::with_offset(b"abc", Style::Xxd, 0x100).to_string(),
+                   "00000100: 6162 63                                  abc\n");
+        assert_eq!(HexDump::new(b"", Style::Canonical).to_string(), "");
+    }
+
+    #[test]
+    fn dump_test() {
+        let mut output = Vec::new();
+
+        {
+            let mut reader = Dump::new(Buffer::new(DATA.to_vec()), &mut output, Style::Xxd);
+
+            big::Read::new(&mut reader).read_u32().expect("unexpected error");
+            reader.read_to_end(&mut Vec::new()).expect("unexpected error");
+            reader.finish().expect("unexpected error");
+        }
+
+        assert_eq!(output, HexDump::new(DATA, Style::Xxd).to_string().into_bytes());
+
+        let mut output = Vec::new();
+
+        {
+            let mut writer = Dump::new(Buffer::new(Vec::new()), &mut output, Style::Canonical);
+
+            big::Write::new(&mut writer).write_u16(0x4142).expect("unexpected error");
+            writer.finish().expect("unexpected error");
+            writer.finish().expect("unexpected error");
+            assert_eq!(**writer, b"AB".to_vec());
+        }
+
+        assert_eq!(output, HexDump::new(b"AB", Style::Canonical).to_string().into_bytes());
+    }
+
+    #[test]
+    fn encode_test() {
+        let mut encoder = super::Encoder::new(Buffer::new(Vec::new()));
+
+        encoder.write_all(&[0x01, 0xAB, 0xFF]).expect("unexpected error");
+        assert_eq!(**encoder, b"01abff".to_vec());
+        assert_eq!(super::encode(b"\x00\x10"), "0010");
+
+        let mut decoder = big::Read::new(super::Decoder::new(Buffer::new(b"01 AB\nff 00"
+                                                                              .to_vec())));
+
+        assert_eq!(decoder.read_u32().expect("unexpected error"), 0x01ABFF00);
+        assert_eq!(super::decode("0a0B").expect("unexpected error"), vec![0x0A, 0x0B]);
+        assert_eq!(super::decode("abc").expect_err("expected error").kind(),
+                   io::ErrorKind::UnexpectedEof);
+        assert_eq!(super::decode("zz").expect_err("expected error").kind(),
+                   io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn dump_error_test() {
+        let mut output = Chunked::new(Vec::new(), Chunking::Fixed(64));
+
+        output.inject(0, io::ErrorKind::BrokenPipe);
+
+        let mut reader = Dump::new(Buffer::new(DATA.to_vec()), output, Style::Xxd);
+        let mut data = Vec::new();
+
+        assert_eq!(reader.read_to_end(&mut data).expect("unexpected error"), DATA.len());
+        assert_eq!(data, DATA.to_vec());
+        assert!(reader.has_error());
+        assert_eq!(reader.finish().expect_err("expected error").kind(),
+                   io::ErrorKind::BrokenPipe);
+        assert_eq!(reader.error().expect("expected error").kind(), io::ErrorKind::BrokenPipe);
+
+        let mut output = Chunked::new(Vec::new(), Chunking::Fixed(64));
+
+        output.inject(0, io::ErrorKind::BrokenPipe);
+
+        let mut writer = Dump::new(Buffer::new(Vec::new()), output, Style::Canonical);
+
+        writer.write_all(b"AB").expect("unexpected error");
+        assert!(!writer.has_error());
+        assert_eq!(writer.finish().expect_err("expected error").kind(),
+                   io::ErrorKind::BrokenPipe);
+        assert!(writer.has_error());
+        assert_eq!(writer.finish().expect_err("expected error").kind(),
+                   io::ErrorKind::BrokenPipe);
+        assert!(writer.output.is_empty());
+    }
+
+    #[test]
+    fn chunked_test() {
+        let mut encoder = super::Encoder::new(testing::blocking(&[1, 4]));
+
+        testing::write_retrying(&mut encoder, &[0x01, 0xAB, 0xFF]).expect("unexpected error");
+        testing::retry(|| encoder.flush()).expect("unexpected error");
+        assert_eq!(encoder.to_vec(), b"01abff".to_vec());
+
+        let mut decoder = super::Decoder::new(Buffer::new(b"0102zz03".to_vec()));
+        let mut buf = [0; 4];
+
+        assert_eq!(decoder.read(&mut buf).expect("unexpected error"), 2);
+        assert_eq!(buf[..2], [1, 2]);
+        assert_eq!(decoder.read(&mut buf).expect_err("expected error").kind(),
+                   io::ErrorKind::InvalidData);
+    }
+}
@@ -1,3 +1,23 @@
+#[cfg(feature = "half")]
+extern crate half;
+#[cfg(feature = "serde")]
+extern crate serde as serde_lib;
+
 pub mod endian;
 pub mod buffer;
 pub mod sticky;
+pub mod bits;
+pub mod codec;
+pub mod fixed;
+pub mod position;
+pub mod error;
+pub mod hash;
+pub mod framing;
+pub mod hex;
+pub mod base;
+pub mod tee;
+pub mod chain;
+pub mod testing;
+mod pending;
+#[cfg(feature = "serde")]
+pub mod serde;
@@ -0,0 +1,60 @@
+//! The pending module provides the output buffer shared by the encoding adapters, which holds
+//! encoded bytes until the wrapped io::Write has accepted all of them.
+//!
+//! An encoder accepts its input once it has been encoded into the buffer, so a short write or an
+//! error such as WouldBlock from the io::Write never causes input to be encoded twice, and the
+//! caller can simply retry.
+
+use std::io;
+
+/// The Pending struct holds encoded bytes that have not yet been written.
+#[derive(Default)]
+pub struct Pending {
+    data: Vec<u8>,
+    sent: usize,
+}
+
+impl Pending {
+    /// Constructs a new, empty, `Pending`.
+    pub fn new() -> Pending {
+        Pending::default()
+    }
+    /// Returns the last byte held, if there is one.
+    pub fn last(&self) -> Option<u8> {
+        self.data.last().cloned()
+    }
+    /// Adds a byte to be written.
+    pub fn push(&mut self, b: u8) {
+        self.data.push(b);
+    }
+    /// Adds the given bytes to be written.
+    pub fn extend_from_slice(&mut self, data: &[u8]) {
+        self.data.extend_from_slice(data);
+    }
+    /// Writes the bytes held to the given io::Write, keeping any that it does not accept for the
+    /// next call.
+    pub fn drain<W: io::Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        while self.sent < self.data.len() {
+            match writer.write(&self.data[self.sent..]) {
+                Ok(0) => {
+                    return Err(io::Error::new(io::ErrorKind::WriteZero,
+                                              "failed to write whole buffer"))
+                }
+                Ok(n) => self.sent += n,
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        self.data.clear();
+        self.sent = 0;
+        Ok(())
+    }
+    /// Writes the bytes held once `n` bytes of input have been encoded into them, returning `n`.
+    ///
+    /// The input has already been accepted, so an error from the io::Write is not returned here,
+    /// but by the next call to `drain`, if it recurs.
+    pub fn accept<W: io::Write>(&mut self, writer: &mut W, n: usize) -> io::Result<usize> {
+        let _ = self.drain(writer);
+        Ok(n)
+    }
+}
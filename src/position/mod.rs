@@ -0,0 +1,159 @@
+use std::io;
+use std::ops;
+
+/// The Position trait is implemented by readers and writers that know their absolute byte
+/// offset within a stream, such as the Counting wrapper and the endian wrappers around it.
+pub trait Position {
+    /// Returns the number of bytes from the start of the stream to the current position.
+    fn position(&self) -> u64;
+}
+
+/// The Counting struct wraps an io::Read and/or io::Write, tracking the absolute byte offset of
+/// all reads and writes that pass through it.
+pub struct Counting<T> {
+    inner: T,
+    position: u64,
+}
+
+impl<T> Counting<T> {
+    /// Constructs a new `Counting<T>`, wrapping the given reader or writer, with a starting
+    /// position of zero.
+    pub fn new(inner: T) -> Counting<T> {
+        Counting::with_position(inner, 0)
+    }
+    /// Constructs a new `Counting<T>`, wrapping the given reader or writer, with the given
+    /// starting position.
+    pub fn with_position(inner: T, position: u64) -> Counting<T> {
+        Counting { inner, position }
+    }
+}
+
+impl<T: io::Seek> Counting<T> {
+    /// Constructs a new `Counting<T>`, wrapping the given seekable reader or writer, with a
+    /// starting position taken from its current position.
+    pub fn from_seek(mut inner: T) -> io::Result<Counting<T>> {
+        let position = inner.stream_position()?;
+        Ok(Counting::with_position(inner, position))
+    }
+}
+
+impl<T> Position for Counting<T> {
+    fn position(&self) -> u64 {
+        self.position
+    }
+}
+
+impl<T: io::Read> io::Read for Counting<T> {
+    /// The read function forwards the call to the embedded io::Read, advancing the position by
+    /// the number of bytes read.
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.position += n as u64;
+        Ok(n)
+    }
+}
+
+impl<T: io::Write> io::Write for Counting<T> {
+    /// The write function forwards the call to the embedded io::Write, advancing the position
+    /// by the number of bytes written.
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.position += n as u64;
+        Ok(n)
+    }
+    /// The flush function forwards the call to the embedded io::Write.
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<T: io::Seek> io::Seek for Counting<T> {
+    /// The seek function forwards the call to the embedded io::Seek, setting the position to the
+    /// one returned.
+    fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+        self.position = self.inner.seek(pos)?;
+        Ok(self.position)
+    }
+}
+
+impl<T> ops::Deref for Counting<T> {
+    type Target = T;
+
+    /// The deref function allows access to the wrapped reader or writer.
+    fn deref(&self) -> &T {
+        &self.inner
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+    use std::io::Seek;
+    use super::super::buffer::Buffer;
+    use super::super::endian::big;
+    use super::super::endian::EndianRead;
+    use super::super::endian::EndianWrite;
+    use super::Counting;
+    use super::Position;
+
+    #[test]
+    fn read_test() {
+        let mut reader = big::Read::new(Counting::new(Buffer::new(vec![1, 2, 3, 4, 5, 6, 7])));
+
+        assert_eq!(reader.position(), 0);
+        reader.read_u16().expect("unexpected error");
+        assert_eq!(reader.position(), 2);
+        reader.read_u32().expect("unexpected error");
+        assert_eq!(reader.position(), 6);
+        assert!(reader.read_u16().is_err());
+        assert_eq!(reader.position(), 7);
+    }
+
+    #[test]
+    fn write_test() {
+        let mut writer = big::Write::new(Counting::with_position(Buffer::new(Vec::new()), 10));
+
+        writer.write_u64(1).expect("unexpected error");
+        writer.write_u8(1).expect("unexpected error");
+        assert_eq!(writer.position(), 19);
+    }
+
+    #[test]
+    fn seek_test() {
+        let mut cursor = io::Cursor::new(vec![0; 16]);
+
+        cursor.seek(io::SeekFrom::Start(4)).expect("unexpected error");
+
+        let mut counting = Counting::from_seek(cursor).expect("unexpected error");
+
+        assert_eq!(counting.position(), 4);
+        counting.seek(io::SeekFrom::Current(8)).expect("unexpected error");
+        assert_eq!(counting.position(), 12);
+    }
+
+    #[test]
+    fn align_test() {
+        let mut reader = big::Read::new(Counting::new(Buffer::new(vec![1, 0, 0, 0, 2, 0, 9, 0,
+                                                                       3])));
+
+        reader.read_u8().expect("unexpected error");
+        reader.align_to_zeroed(4).expect("unexpected error");
+        assert_eq!(reader.position(), 4);
+        reader.align_to(4).expect("unexpected error");
+        assert_eq!(reader.read_u8().expect("unexpected error"), 2);
+        assert_eq!(reader.align_to_zeroed(4).expect_err("expected error").kind(),
+                   io::ErrorKind::InvalidData);
+        reader.align_to(8).expect("unexpected error");
+        assert_eq!(reader.read_u8().expect("unexpected error"), 3);
+        assert!(reader.align_to(0).is_err());
+
+        let mut writer = big::Write::new(Counting::new(Buffer::new(Vec::new())));
+
+        writer.write_u16(1).expect("unexpected error");
+        writer.align_to(4, 0xFF).expect("unexpected error");
+        writer.align_to(4, 0xFF).expect("unexpected error");
+        writer.write_u8(2).expect("unexpected error");
+        writer.align_to(2, 0).expect("unexpected error");
+        assert_eq!(***writer, vec![0, 1, 255, 255, 2, 0]);
+    }
+}
@@ -0,0 +1,651 @@
+//! The serde module provides a Serializer and Deserializer for a compact binary format, using
+//! the byte order of the EndianWrite or EndianRead that they wrap.
+//!
+//! The format is not self-describing, and matches that of the codec module:
+//!
+//! * numbers are written with their natural width, bools as a single byte and chars as their
+//!   32-bit Unicode scalar value;
+//! * strings, byte arrays, sequences and maps have an unsigned 32-bit length prefix;
+//! * options have a single byte tag, 0 for None and 1 for Some;
+//! * tuples and structs are written as their fields in order, with no prefix;
+//! * enum variants are prefixed with their unsigned 32-bit variant index.
+
+use std::error;
+use std::fmt;
+use std::io;
+use std::io::Read;
+use serde_lib::de;
+use serde_lib::ser;
+use serde_lib::Serialize;
+use codec::MAX_PREALLOCATE;
+use endian::EndianRead;
+use endian::EndianWrite;
+
+/// The Error struct wraps an io::Error, so that it can be returned from a Serializer or
+/// Deserializer.
+#[derive(Debug)]
+pub struct Error(io::Error);
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        self.0.source()
+    }
+}
+
+impl ser::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Error {
+        Error(io::Error::new(io::ErrorKind::InvalidData, msg.to_string()))
+    }
+}
+
+impl de::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Error {
+        Error(io::Error::new(io::ErrorKind::InvalidData, msg.to_string()))
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Error {
+        Error(e)
+    }
+}
+
+impl From<Error> for io::Error {
+    fn from(e: Error) -> io::Error {
+        e.0
+    }
+}
+
+/// Serialize the value to the given writer.
+pub fn to_writer<W: EndianWrite, T: Serialize + ?Sized>(w: &mut W, v: &T) -> io::Result<()> {
+    v.serialize(&mut Serializer::new(w))?;
+    Ok(())
+}
+
+/// Deserialize a value of type T from the given reader.
+pub fn from_reader<R: EndianRead, T: de::DeserializeOwned>(r: &mut R) -> io::Result<T> {
+    Ok(T::deserialize(&mut Deserializer::new(r))?)
+}
+
+/// The Serializer struct wraps a mutable reference to an EndianWrite, writing values in its
+/// byte order.
+pub struct Serializer<'a, W: 'a + EndianWrite> {
+    writer: &'a mut W,
+}
+
+impl<'a, W: EndianWrite> Serializer<'a, W> {
+    /// Constructs a new `Serializer`, wrapping the given EndianWrite.
+    pub fn new(writer: &'a mut W) -> Serializer<'a, W> {
+        Serializer { writer }
+    }
+    fn write_length(&mut self, l: usize) -> Result<(), Error> {
+        if l > u32::MAX as usize {
+            return Err(Error(io::Error::new(io::ErrorKind::InvalidInput,
+                                            "length too large for 32-bit prefix")));
+        }
+        Ok(self.writer.write_u32(l as u32)?)
+    }
+}
+
+impl<'a, 'b, W: EndianWrite> ser::Serializer for &'b mut Serializer<'a, W> {
+    type Ok = ();
+    type Error = Error;
+    type SerializeSeq = Self;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Self;
+    type SerializeTupleVariant = Self;
+    type SerializeMap = Self;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Self;
+
+    fn serialize_bool(self, v: bool) -> Result<(), Error> {
+        Ok(self.writer.write_u8(v as u8)?)
+    }
+    fn serialize_i8(self, v: i8) -> Result<(), Error> {
+        Ok(self.writer.write_i8(v)?)
+    }
+    fn serialize_i16(self, v: i16) -> Result<(), Error> {
+        Ok(self.writer.write_i16(v)?)
+    }
+    fn serialize_i32(self, v: i32) -> Result<(), Error> {
+        Ok(self.writer.write_i32(v)?)
+    }
+    fn serialize_i64(self, v: i64) -> Result<(), Error> {
+        Ok(self.writer.write_i64(v)?)
+    }
+    fn serialize_u8(self, v: u8) -> Result<(), Error> {
+        Ok(self.writer.write_u8(v)?)
+    }
+    fn serialize_u16(self, v: u16) -> Result<(), Error> {
+        Ok(self.writer.write_u16(v)?)
+    }
+    fn serialize_u32(self, v: u32) -> Result<(), Error> {
+        Ok(self.writer.write_u32(v)?)
+    }
+    fn serialize_u64(self, v: u64) -> Result<(), Error> {
+        Ok(self.writer.write_u64(v)?)
+    }
+    fn serialize_f32(self, v: f32) -> Result<(), Error> {
+        Ok(self.writer.write_f32(v)?)
+    }
+    fn serialize_f64(self, v: f64) -> Result<(), Error> {
+        Ok(self.writer.write_f64(v)?)
+    }
+    fn serialize_char(self, v: char) -> Result<(), Error> {
+        Ok(self.writer.write_u32(v as u32)?)
+    }
+    fn serialize_str(self, v: &str) -> Result<(), Error> {
+        self.serialize_bytes(v.as_bytes())
+    }
+    fn serialize_bytes(self, v: &[u8]) -> Result<(), Error> {
+        self.write_length(v.len())?;
+        Ok(self.writer.write_all(v)?)
+    }
+    fn serialize_none(self) -> Result<(), Error> {
+        Ok(self.writer.write_u8(0)?)
+    }
+    fn serialize_some<T: Serialize + ?Sized>(self, v: &T) -> Result<(), Error> {
+        self.writer.write_u8(1)?;
+        v.serialize(self)
+    }
+    fn serialize_unit(self) -> Result<(), Error> {
+        Ok(())
+    }
+    fn serialize_unit_struct(self, _: &'static str) -> Result<(), Error> {
+        Ok(())
+    }
+    fn serialize_unit_variant(self,
+                              _: &'static str,
+                              index: u32,
+                              _: &'static str)
+                              -> Result<(), Error> {
+        Ok(self.writer.write_u32(index)?)
+    }
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(self,
+                                                       _: &'static str,
+                                                       v: &T)
+                                                       -> Result<(), Error> {
+        v.serialize(self)
+    }
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(self,
+                                                        _: &'static str,
+                                                        index: u32,
+                                                        _: &'static str,
+                                                        v: &T)
+                                                        -> Result<(), Error> {
+        self.writer.write_u32(index)?;
+        v.serialize(self)
+    }
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self, Error> {
+        match len {
+            Some(l) => {
+                self.write_length(l)?;
+                Ok(self)
+            }
+            None => Err(ser::Error::custom("sequence length must be known")),
+        }
+    }
+    fn serialize_tuple(self, _: usize) -> Result<Self, Error> {
+        Ok(self)
+    }
+    fn serialize_tuple_struct(self, _: &'static str, _: usize) -> Result<Self, Error> {
+        Ok(self)
+    }
+    fn serialize_tuple_variant(self,
+                               _: &'static str,
+                               index: u32,
+                               _: &'static str,
+                               _: usize)
+                               -> Result<Self, Error> {
+        self.writer.write_u32(index)?;
+        Ok(self)
+    }
+    fn serialize_map(self, len: Option<usize>) -> Result<Self, Error> {
+        match len {
+            Some(l) => {
+                self.write_length(l)?;
+                Ok(self)
+            }
+            None => Err(ser::Error::custom("map length must be known")),
+        }
+    }
+    fn serialize_struct(self, _: &'static str, _: usize) -> Result<Self, Error> {
+        Ok(self)
+    }
+    fn serialize_struct_variant(self,
+                                _: &'static str,
+                                index: u32,
+                                _: &'static str,
+                                _: usize)
+                                -> Result<Self, Error> {
+        self.writer.write_u32(index)?;
+        Ok(self)
+    }
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+impl<'a, 'b, W: EndianWrite> ser::SerializeSeq for &'b mut Serializer<'a, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, v: &T) -> Result<(), Error> {
+        v.serialize(&mut **self)
+    }
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl<'a, 'b, W: EndianWrite> ser::SerializeTuple for &'b mut Serializer<'a, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, v: &T) -> Result<(), Error> {
+        v.serialize(&mut **self)
+    }
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl<'a, 'b, W: EndianWrite> ser::SerializeTupleStruct for &'b mut Serializer<'a, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, v: &T) -> Result<(), Error> {
+        v.serialize(&mut **self)
+    }
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl<'a, 'b, W: EndianWrite> ser::SerializeTupleVariant for &'b mut Serializer<'a, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, v: &T) -> Result<(), Error> {
+        v.serialize(&mut **self)
+    }
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl<'a, 'b, W: EndianWrite> ser::SerializeMap for &'b mut Serializer<'a, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, k: &T) -> Result<(), Error> {
+        k.serialize(&mut **self)
+    }
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, v: &T) -> Result<(), Error> {
+        v.serialize(&mut **self)
+    }
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl<'a, 'b, W: EndianWrite> ser::SerializeStruct for &'b mut Serializer<'a, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self,
+                                              _: &'static str,
+                                              v: &T)
+                                              -> Result<(), Error> {
+        v.serialize(&mut **self)
+    }
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl<'a, 'b, W: EndianWrite> ser::SerializeStructVariant for &'b mut Serializer<'a, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self,
+                                              _: &'static str,
+                                              v: &T)
+                                              -> Result<(), Error> {
+        v.serialize(&mut **self)
+    }
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+/// The Deserializer struct wraps a mutable reference to an EndianRead, reading values in its
+/// byte order.
+pub struct Deserializer<'a, R: 'a + EndianRead> {
+    reader: &'a mut R,
+}
+
+impl<'a, R: EndianRead> Deserializer<'a, R> {
+    /// Constructs a new `Deserializer`, wrapping the given EndianRead.
+    pub fn new(reader: &'a mut R) -> Deserializer<'a, R> {
+        Deserializer { reader }
+    }
+    fn read_length(&mut self) -> Result<usize, Error> {
+        Ok(self.reader.read_u32()? as usize)
+    }
+    fn read_bytes(&mut self) -> Result<Vec<u8>, Error> {
+        let l = self.read_length()? as u64;
+        let mut buf = Vec::new();
+        if self.reader.by_ref().take(l).read_to_end(&mut buf)? as u64 != l {
+            return Err(Error(io::Error::new(io::ErrorKind::UnexpectedEof,
+                                            "failed to fill whole buffer")));
+        }
+        Ok(buf)
+    }
+    fn read_string(&mut self) -> Result<String, Error> {
+        match String::from_utf8(self.read_bytes()?) {
+            Ok(s) => Ok(s),
+            Err(e) => Err(Error(io::Error::new(io::ErrorKind::InvalidData, e))),
+        }
+    }
+}
+
+impl<'de, 'a, 'b, R: EndianRead> de::Deserializer<'de> for &'b mut Deserializer<'a, R> {
+    type Error = Error;
+
+    fn deserialize_any<V: de::Visitor<'de>>(self, _: V) -> Result<V::Value, Error> {
+        Err(de::Error::custom("format is not self-describing"))
+    }
+    fn deserialize_bool<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.reader.read_u8()? {
+            0 => visitor.visit_bool(false),
+            1 => visitor.visit_bool(true),
+            v => Err(de::Error::custom(format!("invalid bool: {}", v))),
+        }
+    }
+    fn deserialize_i8<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_i8(self.reader.read_i8()?)
+    }
+    fn deserialize_i16<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_i16(self.reader.read_i16()?)
+    }
+    fn deserialize_i32<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_i32(self.reader.read_i32()?)
+    }
+    fn deserialize_i64<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_i64(self.reader.read_i64()?)
+    }
+    fn deserialize_u8<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_u8(self.reader.read_u8()?)
+    }
+    fn deserialize_u16<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_u16(self.reader.read_u16()?)
+    }
+    fn deserialize_u32<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_u32(self.reader.read_u32()?)
+    }
+    fn deserialize_u64<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_u64(self.reader.read_u64()?)
+    }
+    fn deserialize_f32<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_f32(self.reader.read_f32()?)
+    }
+    fn deserialize_f64<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_f64(self.reader.read_f64()?)
+    }
+    fn deserialize_char<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let v = self.reader.read_u32()?;
+        match ::std::char::from_u32(v) {
+            Some(c) => visitor.visit_char(c),
+            None => Err(de::Error::custom(format!("invalid char: {:#x}", v))),
+        }
+    }
+    fn deserialize_str<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_string(self.read_string()?)
+    }
+    fn deserialize_string<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_string(self.read_string()?)
+    }
+    fn deserialize_bytes<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_byte_buf(self.read_bytes()?)
+    }
+    fn deserialize_byte_buf<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_byte_buf(self.read_bytes()?)
+    }
+    fn deserialize_option<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.reader.read_u8()? {
+            0 => visitor.visit_none(),
+            1 => visitor.visit_some(self),
+            v => Err(de::Error::custom(format!("invalid option tag: {}", v))),
+        }
+    }
+    fn deserialize_unit<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+    fn deserialize_unit_struct<V: de::Visitor<'de>>(self,
+                                                    _: &'static str,
+                                                    visitor: V)
+                                                    -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+    fn deserialize_newtype_struct<V: de::Visitor<'de>>(self,
+                                                       _: &'static str,
+                                                       visitor: V)
+                                                       -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+    fn deserialize_seq<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let l = self.read_length()?;
+        visitor.visit_seq(Access {
+            de: self,
+            remaining: l,
+        })
+    }
+    fn deserialize_tuple<V: de::Visitor<'de>>(self,
+                                              len: usize,
+                                              visitor: V)
+                                              -> Result<V::Value, Error> {
+        visitor.visit_seq(Access {
+            de: self,
+            remaining: len,
+        })
+    }
+    fn deserialize_tuple_struct<V: de::Visitor<'de>>(self,
+                                                     _: &'static str,
+                                                     len: usize,
+                                                     visitor: V)
+                                                     -> Result<V::Value, Error> {
+        self.deserialize_tuple(len, visitor)
+    }
+    fn deserialize_map<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let l = self.read_length()?;
+        visitor.visit_map(Access {
+            de: self,
+            remaining: l,
+        })
+    }
+    fn deserialize_struct<V: de::Visitor<'de>>(self,
+                                               _: &'static str,
+                                               fields: &'static [&'static str],
+                                               visitor: V)
+                                               -> Result<V::Value, Error> {
+        self.deserialize_tuple(fields.len(), visitor)
+    }
+    fn deserialize_enum<V: de::Visitor<'de>>(self,
+                                             _: &'static str,
+                                             _: &'static [&'static str],
+                                             visitor: V)
+                                             -> Result<V::Value, Error> {
+        visitor.visit_enum(self)
+    }
+    fn deserialize_identifier<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_u32(self.reader.read_u32()?)
+    }
+    fn deserialize_ignored_any<V: de::Visitor<'de>>(self, _: V) -> Result<V::Value, Error> {
+        Err(de::Error::custom("format is not self-describing"))
+    }
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+/// The Access struct provides access to a known number of sequence elements or map entries.
+struct Access<'b, 'a: 'b, R: 'a + EndianRead> {
+    de: &'b mut Deserializer<'a, R>,
+    remaining: usize,
+}
+
+impl<'de, 'a, 'b, R: EndianRead> de::SeqAccess<'de> for Access<'b, 'a, R> {
+    type Error = Error;
+
+    fn next_element_seed<T: de::DeserializeSeed<'de>>(&mut self,
+                                                      seed: T)
+                                                      -> Result<Option<T::Value>, Error> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        self.remaining -= 1;
+        Ok(Some(seed.deserialize(&mut *self.de)?))
+    }
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining.min(MAX_PREALLOCATE))
+    }
+}
+
+impl<'de, 'a, 'b, R: EndianRead> de::MapAccess<'de> for Access<'b, 'a, R> {
+    type Error = Error;
+
+    fn next_key_seed<K: de::DeserializeSeed<'de>>(&mut self,
+                                                  seed: K)
+                                                  -> Result<Option<K::Value>, Error> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        self.remaining -= 1;
+        Ok(Some(seed.deserialize(&mut *self.de)?))
+    }
+    fn next_value_seed<V: de::DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        seed.deserialize(&mut *self.de)
+    }
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining.min(MAX_PREALLOCATE))
+    }
+}
+
+impl<'de, 'a, 'b, R: EndianRead> de::EnumAccess<'de> for &'b mut Deserializer<'a, R> {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V: de::DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self), Error> {
+        let index = self.reader.read_u32()?;
+        let v = seed.deserialize(de::IntoDeserializer::<Error>::into_deserializer(index))?;
+        Ok((v, self))
+    }
+}
+
+impl<'de, 'a, 'b, R: EndianRead> de::VariantAccess<'de> for &'b mut Deserializer<'a, R> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        Ok(())
+    }
+    fn newtype_variant_seed<T: de::DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, Error> {
+        seed.deserialize(self)
+    }
+    fn tuple_variant<V: de::Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, Error> {
+        de::Deserializer::deserialize_tuple(self, len, visitor)
+    }
+    fn struct_variant<V: de::Visitor<'de>>(self,
+                                           fields: &'static [&'static str],
+                                           visitor: V)
+                                           -> Result<V::Value, Error> {
+        de::Deserializer::deserialize_tuple(self, fields.len(), visitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate serde_derive;
+
+    use std::collections::BTreeMap;
+    use super::super::buffer::Buffer;
+    use super::super::endian::big;
+    use super::super::endian::little;
+    use self::serde_derive::Deserialize;
+    use self::serde_derive::Serialize;
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    enum Message {
+        Ping,
+        Data(Vec<u8>),
+        Move { x: i16, y: i16 },
+        Pair(char, Option<String>),
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Config {
+        name: String,
+        enabled: bool,
+        ratio: f64,
+        limits: BTreeMap<String, u32>,
+        messages: Vec<Message>,
+        id: (u8, u64),
+    }
+
+    #[test]
+    fn round_trip_test() {
+        let mut limits = BTreeMap::new();
+        limits.insert(String::from("a"), 1);
+        limits.insert(String::from("b"), 2);
+        let config = Config {
+            name: String::from("test"),
+            enabled: true,
+            ratio: 0.25,
+            limits,
+            messages: vec![Message::Ping,
+                           Message::Data(vec![1, 2, 3]),
+                           Message::Move { x: -1, y: 2 },
+                           Message::Pair('x', Some(String::from("y")))],
+            id: (7, 8),
+        };
+        let mut writer = little::Write::new(Buffer::new(Vec::new()));
+
+        super::to_writer(&mut writer, &config).expect("unexpected error");
+
+        let mut reader = little::Read::new(Buffer::new((**writer).clone()));
+
+        assert_eq!(super::from_reader::<_, Config>(&mut reader).expect("unexpected error"),
+                   config);
+    }
+
+    #[test]
+    fn byte_order_test() {
+        let message = Message::Move { x: 1, y: -2 };
+        let mut writer = big::Write::new(Buffer::new(Vec::new()));
+
+        super::to_writer(&mut writer, &message).expect("unexpected error");
+        assert_eq!(**writer, vec![0, 0, 0, 2, 0, 1, 255, 254]);
+
+        let mut writer = little::Write::new(Buffer::new(Vec::new()));
+
+        super::to_writer(&mut writer, &message).expect("unexpected error");
+        assert_eq!(**writer, vec![2, 0, 0, 0, 1, 0, 254, 255]);
+    }
+
+    #[test]
+    fn invalid_test() {
+        let mut reader = big::Read::new(Buffer::new(vec![0, 0, 0, 9]));
+
+        assert!(super::from_reader::<_, Message>(&mut reader).is_err());
+
+        let mut reader = big::Read::new(Buffer::new(vec![2]));
+
+        assert!(super::from_reader::<_, bool>(&mut reader).is_err());
+    }
+}
@@ -18,6 +18,9 @@ impl<T: io::Read> io::Read for Sticky<T> {
                         Ok(c)
                     }
                     Err(e) => {
+                        if e.kind() == io::ErrorKind::Interrupted {
+                            return Err(e);
+                        }
                         self.error = Some(e);
                         Ok(buf.len())
                     }
@@ -39,7 +42,7 @@ impl<T: io::Read> ops::Deref for Sticky<T> {
 impl<T: io::Read> Sticky<T> {
     pub fn new(reader: T) -> Sticky<T> {
         Sticky {
-            reader: reader,
+            reader,
             count: 0,
             error: None,
         }
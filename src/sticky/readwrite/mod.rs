@@ -19,6 +19,9 @@ impl<T: io::Read + io::Write> io::Read for Sticky<T> {
                         Ok(c)
                     }
                     Err(e) => {
+                        if e.kind() == io::ErrorKind::Interrupted {
+                            return Err(e);
+                        }
                         self.error = Some(e);
                         Ok(buf.len())
                     }
@@ -39,6 +42,9 @@ impl<T: io::Read + io::Write> io::Write for Sticky<T> {
                         Ok(c)
                     }
                     Err(e) => {
+                        if e.kind() == io::ErrorKind::Interrupted {
+                            return Err(e);
+                        }
                         self.error = Some(e);
                         Ok(buf.len())
                     }
@@ -18,6 +18,9 @@ impl<T: io::Write> io::Write for Sticky<T> {
                         Ok(c)
                     }
                     Err(e) => {
+                        if e.kind() == io::ErrorKind::Interrupted {
+                            return Err(e);
+                        }
                         self.error = Some(e);
                         Ok(buf.len())
                     }
@@ -42,7 +45,7 @@ impl<T: io::Write> ops::Deref for Sticky<T> {
 impl<T: io::Write> Sticky<T> {
     pub fn new(writer: T) -> Sticky<T> {
         Sticky {
-            writer: writer,
+            writer,
             count: 0,
             error: None,
         }
@@ -0,0 +1,266 @@
+//! The tee module provides adapters that copy a stream to additional writers, recording errors
+//! from those writers in the same way as the sticky module, so that one failing copy does not
+//! interrupt the others.
+
+use std::io;
+use std::ops;
+
+/// The Tee struct wraps an io::Read, writing a copy of every byte read to an io::Write.
+///
+/// Errors from the io::Read are returned as normal. The first error from the io::Write is
+/// stored, after which nothing more is copied to it, and reading continues unaffected.
+pub struct Tee<R: io::Read, W: io::Write> {
+    reader: R,
+    writer: W,
+    count: usize,
+    error: Option<io::Error>,
+}
+
+impl<R: io::Read, W: io::Write> Tee<R, W> {
+    /// Constructs a new `Tee<R, W>`, reading from the given io::Read and copying to the given
+    /// io::Write.
+    pub fn new(reader: R, writer: W) -> Tee<R, W> {
+        Tee {
+            reader,
+            writer,
+            count: 0,
+            error: None,
+        }
+    }
+    /// Returns the io::Write that is being copied to.
+    pub fn writer(&self) -> &W {
+        &self.writer
+    }
+    /// Returns the error from the io::Write, if there has been one.
+    pub fn error(self) -> Option<io::Error> {
+        self.error
+    }
+    /// Returns true if there has been an error from the io::Write.
+    pub fn has_error(&self) -> bool {
+        self.error.is_some()
+    }
+    /// Returns the number of bytes that have been copied to the io::Write.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+    /// Returns the number of bytes that have been copied to the io::Write, or the error from it.
+    pub fn result(self) -> io::Result<usize> {
+        match self.error {
+            Some(e) => Err(e),
+            None => Ok(self.count),
+        }
+    }
+}
+
+impl<R: io::Read, W: io::Write> io::Read for Tee<R, W> {
+    /// The read function forwards the call to the embedded io::Read, copying the bytes read to
+    /// the embedded io::Write.
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.reader.read(buf)?;
+        if self.error.is_none() {
+            match self.writer.write_all(&buf[..n]) {
+                Ok(()) => self.count += n,
+                Err(e) => self.error = Some(e),
+            }
+        }
+        Ok(n)
+    }
+}
+
+impl<R: io::Read, W: io::Write> ops::Deref for Tee<R, W> {
+    type Target = R;
+
+    /// The deref function allows access to the wrapped io::Read.
+    fn deref(&self) -> &R {
+        &self.reader
+    }
+}
+
+struct Branch<'a> {
+    writer: Box<dyn io::Write + 'a>,
+    count: usize,
+    error: Option<io::Error>,
+}
+
+/// The MultiWriter struct writes a copy of everything written to it to each of a number of
+/// branches.
+///
+/// The first error from each branch is stored, after which nothing more is written to that
+/// branch, and writing to the other branches continues unaffected. As with the sticky wrappers,
+/// writing to a MultiWriter never fails, and errors are checked once writing is complete.
+#[derive(Default)]
+pub struct MultiWriter<'a> {
+    branches: Vec<Branch<'a>>,
+    count: usize,
+}
+
+impl<'a> MultiWriter<'a> {
+    /// Constructs a new `MultiWriter`, with no branches.
+    pub fn new() -> MultiWriter<'a> {
+        MultiWriter::default()
+    }
+    /// Adds the given io::Write as a new branch, returning its index.
+    ///
+    /// The branch only receives data written after it is added.
+    pub fn push<W: io::Write + 'a>(&mut self, writer: W) -> usize {
+        self.branches.push(Branch {
+            writer: Box::new(writer),
+            count: 0,
+            error: None,
+        });
+        self.branches.len() - 1
+    }
+    /// Returns the number of branches.
+    pub fn len(&self) -> usize {
+        self.branches.len()
+    }
+    /// Returns true if there are no branches.
+    pub fn is_empty(&self) -> bool {
+        self.branches.is_empty()
+    }
+    /// Returns the number of bytes that have been written to the branch with the given index.
+    pub fn count(&self, index: usize) -> usize {
+        self.branches[index].count
+    }
+    /// Returns the error from the branch with the given index, if there has been one.
+    pub fn error(&self, index: usize) -> Option<&io::Error> {
+        self.branches[index].error.as_ref()
+    }
+    /// Returns true if there has been an error from any branch.
+    pub fn has_error(&self) -> bool {
+        self.branches.iter().any(|b| b.error.is_some())
+    }
+    /// Returns the number of bytes that have been written, or the error from the first branch,
+    /// by index, that has had one.
+    pub fn result(self) -> io::Result<usize> {
+        for branch in self.branches {
+            if let Some(e) = branch.error {
+                return Err(e);
+            }
+        }
+        Ok(self.count)
+    }
+}
+
+impl<'a> io::Write for MultiWriter<'a> {
+    /// The write function writes all of the given bytes to each branch that has not had an
+    /// error, always returning the length of the given slice.
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        for branch in &mut self.branches {
+            if branch.error.is_none() {
+                match branch.writer.write_all(buf) {
+                    Ok(()) => branch.count += buf.len(),
+                    Err(e) => branch.error = Some(e),
+                }
+            }
+        }
+        self.count += buf.len();
+        Ok(buf.len())
+    }
+    /// The flush function flushes each branch that has not had an error, storing any error,
+    /// and always returning Ok(()).
+    fn flush(&mut self) -> io::Result<()> {
+        for branch in &mut self.branches {
+            if branch.error.is_none() {
+                if let Err(e) = branch.writer.flush() {
+                    branch.error = Some(e);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+    use std::io::Read;
+    use std::io::Write;
+    use super::super::buffer::Buffer;
+    use super::super::endian::big;
+    use super::super::endian::EndianRead;
+    use super::super::endian::EndianWrite;
+    use super::super::hash::Crc32;
+    use super::super::hash::Hashing;
+    use super::MultiWriter;
+    use super::Tee;
+
+    struct Limited(usize);
+
+    impl io::Write for Limited {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            if self.0 == 0 {
+                return Err(io::Error::new(io::ErrorKind::WriteZero, "full"));
+            }
+            let n = if buf.len() < self.0 { buf.len() } else { self.0 };
+            self.0 -= n;
+            Ok(n)
+        }
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn tee_test() {
+        let mut log = Vec::new();
+
+        {
+            let mut reader = big::Read::new(Tee::new(Buffer::new(vec![1, 2, 3, 4, 5]), &mut log));
+
+            assert_eq!(reader.read_u32().expect("unexpected error"), 0x01020304);
+            assert_eq!(reader.count(), 4);
+        }
+
+        assert_eq!(log, vec![1, 2, 3, 4]);
+
+        let mut tee = Tee::new(Buffer::new(vec![1, 2, 3, 4, 5]), Limited(3));
+        let mut buf = Vec::new();
+
+        tee.read_to_end(&mut buf).expect("unexpected error");
+        assert_eq!(buf, vec![1, 2, 3, 4, 5]);
+        assert!(tee.has_error());
+        assert_eq!(tee.result().expect_err("expected error").kind(),
+                   io::ErrorKind::WriteZero);
+    }
+
+    #[test]
+    fn multi_writer_test() {
+        let mut hasher = Hashing::new(io::sink(), Crc32::new());
+        let mut log = Vec::new();
+        let mut forward = Buffer::new(Vec::new());
+
+        {
+            let mut multi = MultiWriter::new();
+
+            multi.push(&mut hasher);
+            multi.push(&mut log);
+
+            let limited = multi.push(Limited(2));
+
+            multi.push(&mut forward);
+
+            let mut writer = big::Write::new(&mut multi);
+
+            writer.write_all(b"IE").expect("unexpected error");
+            writer.write_u16(0x4E44).expect("unexpected error");
+            assert_eq!(writer.len(), 4);
+            assert!(writer.has_error());
+            assert_eq!(writer.count(limited), 2);
+            assert_eq!(writer.count(0), 4);
+            assert!(writer.error(0).is_none());
+            assert_eq!(writer.error(limited).expect("expected error").kind(),
+                       io::ErrorKind::WriteZero);
+        }
+
+        assert_eq!(hasher.finish(), 0xAE426082);
+        assert_eq!(log, b"IEND".to_vec());
+        assert_eq!(*forward, b"IEND".to_vec());
+
+        let mut multi = MultiWriter::new();
+
+        multi.push(Vec::new());
+        big::Write::new(&mut multi).write_u64(0).expect("unexpected error");
+        assert_eq!(multi.result().expect("unexpected error"), 8);
+    }
+}
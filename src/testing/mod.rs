@@ -0,0 +1,250 @@
+//! The testing module provides adapters that make IO less well behaved than an in-memory
+//! Buffer, returning short reads and writes and injecting errors, so that code can be tested
+//! against the partial IO that files, pipes and sockets can produce.
+
+use std::io;
+use std::ops;
+use buffer::Buffer;
+use position::Position;
+
+/// The Chunking enum determines how many bytes a Chunked wrapper allows each call to transfer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Chunking {
+    /// Each call transfers at most the given number of bytes. A limit of zero is treated as one.
+    Fixed(usize),
+    /// Each call transfers between one and `max` bytes, chosen by a pseudo-random generator
+    /// started from `seed`, so that a failing run can be repeated.
+    Random { seed: u64, max: usize },
+}
+
+/// The Chunked struct wraps an io::Read and/or io::Write, limiting the number of bytes each call
+/// transfers and returning injected errors at given positions.
+pub struct Chunked<T> {
+    inner: T,
+    chunking: Chunking,
+    state: u64,
+    position: u64,
+    faults: Vec<(u64, io::ErrorKind)>,
+}
+
+impl<T> Chunked<T> {
+    /// Constructs a new `Chunked<T>`, wrapping the given reader or writer and limiting calls as
+    /// given.
+    pub fn new(inner: T, chunking: Chunking) -> Chunked<T> {
+        let state = match chunking {
+            Chunking::Random { seed, .. } => seed,
+            Chunking::Fixed(_) => 0,
+        };
+        Chunked {
+            inner,
+            chunking,
+            state,
+            position: 0,
+            faults: Vec::new(),
+        }
+    }
+    /// Arranges for the call that would transfer the byte at the given position to return an
+    /// error of the given kind instead, such as Interrupted or WouldBlock.
+    ///
+    /// Calls never transfer bytes past a pending error, and each error is returned only once, so
+    /// the following call continues from the same position.
+    pub fn inject(&mut self, position: u64, kind: io::ErrorKind) {
+        self.faults.push((position, kind));
+    }
+    fn next_random(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+    fn limit(&mut self, len: usize) -> io::Result<usize> {
+        let position = self.position;
+        if let Some(i) = self.faults.iter().position(|&(p, _)| p <= position) {
+            let (_, kind) = self.faults.remove(i);
+            return Err(io::Error::new(kind, "injected error"));
+        }
+        let mut n = match self.chunking {
+            Chunking::Fixed(n) => n.max(1),
+            Chunking::Random { max, .. } => 1 + (self.next_random() % max.max(1) as u64) as usize,
+        };
+        if let Some(next) = self.faults.iter().map(|&(p, _)| p).min() {
+            if next - position < n as u64 {
+                n = (next - position) as usize;
+            }
+        }
+        Ok(n.min(len))
+    }
+}
+
+impl<T> Position for Chunked<T> {
+    fn position(&self) -> u64 {
+        self.position
+    }
+}
+
+impl<T: io::Read> io::Read for Chunked<T> {
+    /// The read function forwards the call to the embedded io::Read, limiting the length of the
+    /// read, or returns an injected error.
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.limit(buf.len())?;
+        let n = self.inner.read(&mut buf[..n])?;
+        self.position += n as u64;
+        Ok(n)
+    }
+}
+
+impl<T: io::Write> io::Write for Chunked<T> {
+    /// The write function forwards the call to the embedded io::Write, limiting the length of
+    /// the write, or returns an injected error.
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.limit(buf.len())?;
+        let n = self.inner.write(&buf[..n])?;
+        self.position += n as u64;
+        Ok(n)
+    }
+    /// The flush function forwards the call to the embedded io::Write.
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<T> ops::Deref for Chunked<T> {
+    type Target = T;
+
+    /// The deref function allows access to the wrapped reader or writer.
+    fn deref(&self) -> &T {
+        &self.inner
+    }
+}
+
+/// Returns a Chunked Buffer that accepts one byte per write, and returns WouldBlock from the
+/// write that would transfer the byte at each of the given positions.
+pub fn blocking(positions: &[u64]) -> Chunked<Buffer> {
+    let mut chunked = Chunked::new(Buffer::new(Vec::new()), Chunking::Fixed(1));
+    for &p in positions {
+        chunked.inject(p, io::ErrorKind::WouldBlock);
+    }
+    chunked
+}
+
+/// Calls the given function until it returns something other than a WouldBlock or Interrupted
+/// error, as a caller waiting for a non-blocking stream to become ready would.
+pub fn retry<T, F: FnMut() -> io::Result<T>>(mut f: F) -> io::Result<T> {
+    loop {
+        match f() {
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock ||
+                          e.kind() == io::ErrorKind::Interrupted => {}
+            result => return result,
+        }
+    }
+}
+
+/// Writes all of the given bytes to the io::Write, using `retry` for each call to write.
+pub fn write_retrying<W: io::Write>(writer: &mut W, mut data: &[u8]) -> io::Result<()> {
+    while !data.is_empty() {
+        let n = retry(|| writer.write(data))?;
+        if n == 0 {
+            return Err(io::Error::new(io::ErrorKind::WriteZero, "failed to write whole buffer"));
+        }
+        data = &data[n..];
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+    use std::io::Read;
+    use std::io::Write;
+    use super::super::buffer::Buffer;
+    use super::super::endian::big;
+    use super::super::endian::EndianRead;
+    use super::super::endian::EndianWrite;
+    use super::super::position::Position;
+    use super::super::sticky::write::Sticky;
+    use super::Chunked;
+    use super::Chunking;
+
+    #[test]
+    fn fixed_test() {
+        let mut chunked = Chunked::new(Buffer::new(vec![1, 2, 3, 4, 5, 6, 7, 8]),
+                                       Chunking::Fixed(1));
+
+        chunked.inject(2, io::ErrorKind::Interrupted);
+        chunked.inject(4, io::ErrorKind::WouldBlock);
+
+        let mut reader = big::Read::new(chunked);
+
+        assert_eq!(reader.read_u32().expect("unexpected error"), 0x01020304);
+        assert_eq!(reader.read_u32().expect_err("expected error").kind(),
+                   io::ErrorKind::WouldBlock);
+        assert_eq!(reader.position(), 4);
+        assert_eq!(reader.read_u32().expect("unexpected error"), 0x05060708);
+        assert_eq!(reader.read(&mut [0; 4]).expect("unexpected error"), 0);
+    }
+
+    #[test]
+    fn random_test() {
+        let sizes = |seed| {
+            let mut chunked = Chunked::new(Buffer::new(vec![0; 64]),
+                                           Chunking::Random { seed, max: 5 });
+            let mut sizes = Vec::new();
+            let mut buf = [0; 8];
+
+            loop {
+                match chunked.read(&mut buf).expect("unexpected error") {
+                    0 => return sizes,
+                    n => sizes.push(n),
+                }
+            }
+        };
+
+        assert_eq!(sizes(1), sizes(1));
+        assert_ne!(sizes(1), sizes(2));
+        assert!(sizes(3).iter().all(|&n| (1..=5).contains(&n)));
+        assert_eq!(sizes(4).iter().sum::<usize>(), 64);
+    }
+
+    #[test]
+    fn sticky_test() {
+        let data: Vec<u8> = (0..100).collect();
+        let mut chunked = Chunked::new(Buffer::new(Vec::new()),
+                                       Chunking::Random { seed: 7, max: 3 });
+
+        chunked.inject(10, io::ErrorKind::Interrupted);
+
+        let mut writer = big::Write::new(Sticky::new(chunked));
+
+        writer.write_all(&data).expect("unexpected error");
+        writer.write_u32(0x01020304).expect("unexpected error");
+        assert!(!writer.has_error());
+        assert_eq!(writer.count(), 104);
+        assert_eq!(&writer[..100], &data[..]);
+        assert_eq!(&writer[100..], &[1, 2, 3, 4]);
+
+        let mut chunked = Chunked::new(Buffer::new(Vec::new()), Chunking::Fixed(2));
+
+        chunked.inject(3, io::ErrorKind::BrokenPipe);
+
+        let mut writer = Sticky::new(chunked);
+
+        writer.write_all(&data[..8]).expect("unexpected error");
+        assert_eq!(writer.count(), 3);
+        assert_eq!(writer.error().expect("expected error").kind(),
+                   io::ErrorKind::BrokenPipe);
+    }
+
+    #[test]
+    fn retry_test() {
+        let mut writer = super::blocking(&[0, 2, 3]);
+
+        super::write_retrying(&mut writer, b"abcd").expect("unexpected error");
+        assert_eq!(**writer, b"abcd".to_vec());
+
+        let mut writer = super::blocking(&[0]);
+
+        assert_eq!(super::retry(|| writer.write(b"ab")).expect("unexpected error"), 1);
+        assert_eq!(writer.position(), 1);
+    }
+}